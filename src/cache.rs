@@ -1,57 +1,515 @@
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex as StdMutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use sanitize_filename::sanitize;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 
-use crate::{config::cache_path, error::LyricsError, song::SongInfo};
+use crate::{
+    client::{LyricsContent, LyricsItem},
+    config::{cache_path, get_config, legacy_cache_path},
+    error::LyricsError,
+    song::SongInfo,
+};
+
+/// 缓存索引文件
+const INDEX_FILE: &str = "index.json";
+
+/// 缓存文件的后缀，较长的在前
+const CACHE_SUFFIXES: [&str; 4] = [".trans.lrc", ".roma.lrc", ".lrc", ".offset"];
+
+/// 候选参数中内联的歌词，已保存为缓存文件，不写入索引
+const INLINE_PARAMS: [&str; 3] = ["lyric", "translation", "romanization"];
+
+/// 缓存索引，键为缓存文件名 (不含扩展名)
+pub type CacheIndex = BTreeMap<String, CacheEntry>;
+
+/// 缓存索引记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheEntry {
+    /// 缓存时的歌曲信息
+    pub song: CachedSong,
+    /// 歌词来源，只保存了偏移时为空
+    pub source: Option<String>,
+    /// 候选参数，如歌曲 id
+    pub params: Vec<(String, String)>,
+    /// 获取时间，Unix 时间戳，单位：秒
+    pub fetched_at: u64,
+    /// 最近读取时间，Unix 时间戳，单位：秒，用于按最近访问清理
+    pub accessed_at: u64,
+    /// 自动匹配的评分，手动下载时为空
+    pub score: Option<f64>,
+    /// 歌词偏移，单位：毫秒，正数表示歌词提前
+    pub offset: i64,
+    /// 最近一次获取时所有来源都没有歌词
+    pub not_found: bool,
+}
+
+/// 索引中保存的歌曲信息
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CachedSong {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// 时长，单位：秒
+    pub duration: f64,
+    pub url: String,
+}
+
+impl From<&SongInfo> for CachedSong {
+    fn from(song: &SongInfo) -> Self {
+        Self {
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            duration: song.duration,
+            url: song.url.clone(),
+        }
+    }
+}
+
+/// 缓存清理结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GcReport {
+    /// 删除的歌曲数
+    pub removed: usize,
+    /// 释放的空间，单位：字节
+    pub freed: u64,
+    /// 清理后的缓存大小，单位：字节
+    pub size: u64,
+}
+
+/// 同一首歌的缓存文件
+#[derive(Debug, Default)]
+struct CacheGroup {
+    files: Vec<PathBuf>,
+    size: u64,
+    /// 最近访问时间，Unix 时间戳，单位：秒
+    accessed_at: u64,
+}
 
 // 缓存管理模块
 #[derive(Debug, Clone, Default)]
 pub struct CacheManager {
     base_dir: PathBuf,
+    /// 串行化索引与缓存文件的修改
+    index_lock: Arc<Mutex<()>>,
+    /// 尚未写入索引的访问时间，读取歌词时只记录在内存中，下次修改索引时一并写入
+    accessed: Arc<StdMutex<HashMap<String, u64>>>,
+    /// 缓存的最大大小，单位：字节，0 表示不限制
+    max_size: u64,
+    /// 未访问的最长时间，单位：秒，0 表示不限制
+    max_age: u64,
 }
 
 impl CacheManager {
     pub fn new() -> Self {
+        let config = &get_config().read().unwrap().cache;
+        let cache = Self::with_dir(cache_path())
+            .with_limits(config.max_size * 1024 * 1024, config.max_age * 24 * 60 * 60);
+        cache.migrate_legacy(&legacy_cache_path());
+        cache
+    }
+
+    /// 使用指定目录作为缓存目录，不限制大小
+    pub fn with_dir(base_dir: PathBuf) -> Self {
         Self {
-            base_dir: cache_path(),
+            base_dir,
+            ..Default::default()
+        }
+    }
+
+    /// 设置清理上限，max_size 单位：字节，max_age 单位：秒，0 表示不限制
+    pub fn with_limits(mut self, max_size: u64, max_age: u64) -> Self {
+        self.max_size = max_size;
+        self.max_age = max_age;
+        self
+    }
+
+    /// 将旧版目录中的缓存文件与索引移动到缓存目录，返回移动的文件数
+    pub fn migrate_legacy(&self, legacy_dir: &Path) -> usize {
+        let Ok(entries) = std::fs::read_dir(legacy_dir) else {
+            return 0;
+        };
+        let mut moved = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let is_cache = name == INDEX_FILE || CACHE_SUFFIXES.iter().any(|s| name.ends_with(s));
+            if !is_cache || !entry.path().is_file() {
+                continue;
+            }
+            match std::fs::rename(entry.path(), self.base_dir.join(&*name)) {
+                Ok(_) => moved += 1,
+                Err(e) => log::warn!("Move cache {} failed: {}", name, e),
+            }
         }
+        if moved > 0 {
+            log::info!("Moved {} cache files to {}", moved, self.base_dir.display());
+        }
+        moved
     }
 
     fn lyrics_name(&self, song: &SongInfo) -> PathBuf {
+        self.cache_file(song, "lrc")
+    }
+
+    /// 翻译歌词文件
+    fn translation_name(&self, song: &SongInfo) -> PathBuf {
+        self.cache_file(song, "trans.lrc")
+    }
+
+    /// 音译歌词文件
+    fn romanization_name(&self, song: &SongInfo) -> PathBuf {
+        self.cache_file(song, "roma.lrc")
+    }
+
+    /// 旧版的歌词偏移文件，读取后迁移到索引
+    fn offset_name(&self, song: &SongInfo) -> PathBuf {
+        self.cache_file(song, "offset")
+    }
+
+    fn cache_file(&self, song: &SongInfo, ext: &str) -> PathBuf {
+        let file_name = format!("{}.{}", Self::key(song), ext);
+        let mut path = self.base_dir.clone();
+        path.push(file_name);
+        path
+    }
+
+    /// 索引键，与缓存文件名一致
+    fn key(song: &SongInfo) -> String {
         let mut name = vec![sanitize(&song.artist), sanitize(&song.title)];
         if !song.album.is_empty() {
             name.push(sanitize(&song.album));
         }
-        let file_name = format!("{}.lrc", name.join("-"));
-        let mut path = self.base_dir.clone();
-        path.push(file_name);
-        path
+        name.join("-")
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.base_dir.join(INDEX_FILE)
+    }
+
+    /// 读取缓存索引，包含尚未写入的访问时间，不存在或无法解析时为空
+    pub async fn index(&self) -> CacheIndex {
+        let mut index = self.read_index().await;
+        self.apply_accessed(&mut index, false);
+        index
     }
 
-    pub async fn get(&self, song: &SongInfo) -> Option<String> {
+    async fn read_index(&self) -> CacheIndex {
+        let Ok(data) = tokio::fs::read(self.index_path()).await else {
+            return CacheIndex::new();
+        };
+        serde_json::from_slice(&data).unwrap_or_else(|e| {
+            log::warn!("Invalid cache index: {}", e);
+            CacheIndex::new()
+        })
+    }
+
+    /// 将内存中的访问时间合并到索引，take 为 true 时清空，由写回索引的一方调用
+    fn apply_accessed(&self, index: &mut CacheIndex, take: bool) {
+        let mut accessed = self.accessed.lock().unwrap();
+        let pending = match take {
+            true => std::mem::take(&mut *accessed),
+            false => accessed.clone(),
+        };
+        for (key, time) in pending {
+            if let Some(entry) = index.get_mut(&key) {
+                entry.accessed_at = entry.accessed_at.max(time);
+            }
+        }
+    }
+
+    /// 歌曲的索引记录
+    pub async fn entry(&self, song: &SongInfo) -> Option<CacheEntry> {
+        self.index().await.remove(&Self::key(song))
+    }
+
+    /// 修改索引并写回
+    async fn update_index(&self, f: impl FnOnce(&mut CacheIndex)) -> Result<(), LyricsError> {
+        let _guard = self.index_lock.lock().await;
+        let mut index = self.take_index().await;
+        f(&mut index);
+        self.save_index(&index).await
+    }
+
+    /// 读取索引并取出内存中的访问时间，需持有 index_lock
+    async fn take_index(&self) -> CacheIndex {
+        let mut index = self.read_index().await;
+        self.apply_accessed(&mut index, true);
+        index
+    }
+
+    /// 写回索引，先写入临时文件再替换，需持有 index_lock
+    async fn save_index(&self, index: &CacheIndex) -> Result<(), LyricsError> {
+        tokio::fs::create_dir_all(&self.base_dir).await?;
+        let data = serde_json::to_vec_pretty(index).map_err(anyhow::Error::from)?;
+        let tmp = self.index_path().with_extension("json.tmp");
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(tmp, self.index_path()).await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, song: &SongInfo) -> Option<LyricsContent> {
         let path = self.lyrics_name(song);
         if !path.exists() {
             return None;
         }
-        tokio::fs::read_to_string(&path).await.ok()
+        let lyric = tokio::fs::read_to_string(&path).await.ok()?;
+        let translation = tokio::fs::read_to_string(self.translation_name(song))
+            .await
+            .ok();
+        let romanization = tokio::fs::read_to_string(self.romanization_name(song))
+            .await
+            .ok();
+
+        self.accessed.lock().unwrap().insert(Self::key(song), now());
+
+        Some(LyricsContent {
+            lyric,
+            translation,
+            romanization,
+        })
     }
 
+    /// 保存歌词及其来源，score 为自动匹配的评分
     pub async fn store(
         &self,
         song: &SongInfo,
-        _source: &str,
-        content: &str,
+        item: &LyricsItem,
+        score: Option<f64>,
+        content: &LyricsContent,
     ) -> Result<(), LyricsError> {
+        let offset = self.get_offset(song).await;
+        // 写入歌词与索引期间持有锁，避免清理删除刚写入的歌词
+        let _guard = self.index_lock.lock().await;
+        tokio::fs::create_dir_all(&self.base_dir).await?;
         let path = self.lyrics_name(song);
-        tokio::fs::write(path, &content).await?;
-        Ok(())
+        tokio::fs::write(path, &content.lyric).await?;
+
+        let tracks = [
+            (self.translation_name(song), &content.translation),
+            (self.romanization_name(song), &content.romanization),
+        ];
+        for (path, track) in tracks {
+            match track {
+                Some(track) => tokio::fs::write(path, track).await?,
+                None if path.exists() => tokio::fs::remove_file(path).await?,
+                None => {}
+            }
+        }
+
+        let entry = CacheEntry {
+            song: song.into(),
+            source: Some(item.source.clone()),
+            params: item
+                .params
+                .iter()
+                .filter(|(k, _)| !INLINE_PARAMS.contains(&k.as_str()))
+                .cloned()
+                .collect(),
+            fetched_at: now(),
+            accessed_at: now(),
+            score,
+            offset,
+            not_found: false,
+        };
+        let mut index = self.take_index().await;
+        index.insert(Self::key(song), entry);
+        self.save_index(&index).await?;
+        self.remove_offset_file(song).await
+    }
+
+    /// 记录所有来源都没有歌词，保留偏移，已有缓存歌词时不记录
+    pub async fn store_not_found(&self, song: &SongInfo) -> Result<(), LyricsError> {
+        let key = Self::key(song);
+        let offset = self.get_offset(song).await;
+        self.update_index(|index| {
+            let entry = index.entry(key).or_default();
+            if entry.source.is_some() {
+                return;
+            }
+            *entry = CacheEntry {
+                song: song.into(),
+                fetched_at: now(),
+                accessed_at: now(),
+                offset,
+                not_found: true,
+                ..Default::default()
+            };
+        })
+        .await
+    }
+
+    /// 未找到歌词的记录是否仍在有效期内，ttl 单位：秒
+    pub async fn is_not_found(&self, song: &SongInfo, ttl: u64) -> bool {
+        self.entry(song)
+            .await
+            .is_some_and(|entry| entry.not_found && now().saturating_sub(entry.fetched_at) < ttl)
+    }
+
+    /// 获取歌曲的偏移，单位：毫秒，索引中没有时读取旧版偏移文件，读取索引记录视为访问
+    pub async fn get_offset(&self, song: &SongInfo) -> i64 {
+        if let Some(entry) = self.entry(song).await {
+            self.accessed.lock().unwrap().insert(Self::key(song), now());
+            return entry.offset;
+        }
+        let path = self.offset_name(song);
+        if !path.exists() {
+            return 0;
+        }
+        tokio::fs::read_to_string(&path)
+            .await
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存歌曲的偏移到索引，记录中没有其他信息且偏移为 0 时删除记录
+    pub async fn store_offset(&self, song: &SongInfo, offset: i64) -> Result<(), LyricsError> {
+        let key = Self::key(song);
+        self.update_index(|index| {
+            let entry = index.entry(key.clone()).or_insert_with(|| CacheEntry {
+                song: song.into(),
+                ..Default::default()
+            });
+            entry.offset = offset;
+            entry.accessed_at = now();
+            if entry.source.is_none() && !entry.not_found && offset == 0 {
+                index.remove(&key);
+            }
+        })
+        .await?;
+        self.remove_offset_file(song).await
+    }
+
+    async fn remove_offset_file(&self, song: &SongInfo) -> Result<(), LyricsError> {
+        match tokio::fs::remove_file(self.offset_name(song)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// 按上限清理缓存：先删除超过 max_age 未访问的歌曲，
+    /// 总大小仍超过 max_size 时按最近访问时间从旧到新删除，同时写入内存中的访问时间
+    pub async fn gc(&self) -> Result<GcReport, LyricsError> {
+        // 选择与删除期间持有锁，不会删除正在写入的歌词
+        let _guard = self.index_lock.lock().await;
+        let mut index = self.take_index().await;
+        let mut groups = self.cache_groups().await?;
+        // 有索引记录时以记录的访问时间为准，否则使用文件修改时间
+        for (key, entry) in &index {
+            groups.entry(key.clone()).or_default().accessed_at =
+                entry.accessed_at.max(entry.fetched_at);
+        }
+
+        let mut groups: Vec<(String, CacheGroup)> = groups.into_iter().collect();
+        groups.sort_by_key(|(_, group)| group.accessed_at);
+
+        let now = now();
+        let mut report = GcReport {
+            size: groups.iter().map(|(_, group)| group.size).sum(),
+            ..Default::default()
+        };
+        for (key, group) in groups {
+            let expired = self.max_age > 0 && now.saturating_sub(group.accessed_at) > self.max_age;
+            let oversize = self.max_size > 0 && report.size > self.max_size;
+            // 只有索引记录 (如偏移) 的歌曲不占空间，只按时间过期，不计入清理结果
+            if group.files.is_empty() {
+                if expired {
+                    index.remove(&key);
+                }
+                continue;
+            }
+            if !expired && !oversize {
+                continue;
+            }
+            // 删除失败时保留记录，下次清理再试，其他歌曲照常清理
+            let mut failed = false;
+            for file in &group.files {
+                match tokio::fs::remove_file(file).await {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        log::warn!("Failed to remove cache file {:?}: {}", file, e);
+                        failed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if failed {
+                continue;
+            }
+            log::debug!("Cache gc removed: {}", key);
+            report.removed += 1;
+            report.freed += group.size;
+            report.size -= group.size;
+            index.remove(&key);
+        }
+
+        self.save_index(&index).await?;
+        log::info!("Cache gc: {:?}", report);
+        Ok(report)
+    }
+
+    /// 按歌曲分组的缓存文件
+    async fn cache_groups(&self) -> Result<HashMap<String, CacheGroup>, LyricsError> {
+        let mut groups: HashMap<String, CacheGroup> = HashMap::new();
+        let Ok(mut entries) = tokio::fs::read_dir(&self.base_dir).await else {
+            return Ok(groups);
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(key) = CACHE_SUFFIXES.iter().find_map(|s| name.strip_suffix(s)) else {
+                continue;
+            };
+            let metadata = entry.metadata().await?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+
+            let group = groups.entry(key.to_string()).or_default();
+            group.files.push(entry.path());
+            group.size += metadata.len();
+            group.accessed_at = group.accessed_at.max(modified);
+        }
+        Ok(groups)
     }
 
     pub async fn delete(&self, song: &SongInfo) {
+        let _guard = self.index_lock.lock().await;
         let path = self.lyrics_name(song);
         match tokio::fs::remove_file(path).await {
             Ok(_) => {}
             Err(e) => log::error!("delete file {} failed {}", song.title, e),
         }
+        for path in [self.translation_name(song), self.romanization_name(song)] {
+            if path.exists()
+                && let Err(e) = tokio::fs::remove_file(path).await
+            {
+                log::error!("delete track {} failed {}", song.title, e);
+            }
+        }
+        if let Err(e) = self.remove_offset_file(song).await {
+            log::error!("delete offset {} failed {}", song.title, e);
+        }
+        let mut index = self.take_index().await;
+        index.remove(&Self::key(song));
+        if let Err(e) = self.save_index(&index).await {
+            log::error!("delete index {} failed {}", song.title, e);
+        }
     }
 }
+
+/// 当前 Unix 时间戳，单位：秒
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
@@ -1,29 +1,55 @@
-use std::sync::OnceLock;
+use std::{sync::OnceLock, time::Duration};
 
 use async_trait::async_trait;
+use command::CommandFetcher;
+use embedded::EmbeddedFetcher;
 use kugou::KugouFetcher;
+use local::LocalFetcher;
+use lrclib::LrclibFetcher;
 use netease::NeteaseFetcher;
+use ovh::OvhFetcher;
 use qqmusic::QQMusicFetcher;
-use reqwest::RequestBuilder;
+use reqwest::{
+    RequestBuilder,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use score::{Score, Scorer};
 use serde::de::DeserializeOwned;
+use tokio::time::{Instant, timeout_at};
 
 use crate::{
-    cache::CacheManager, config::get_config, error::LyricsError, song::SongInfo,
+    cache::CacheManager,
+    config::{SourceSettings, Sources, get_config},
+    error::LyricsError,
+    song::{LyricParser, SongInfo, SyltParser},
     utils::normalize_text,
 };
 
+mod command;
+mod embedded;
 mod kugou;
+mod local;
+mod lrclib;
 mod netease;
 mod ovh;
 mod qqmusic;
+mod score;
 
 /// 歌词抓取器
 #[async_trait]
 trait LyricsFetcher: Send + Sync {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError>;
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError>;
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError>;
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError>;
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError>;
     fn source_name(&self) -> &'static str;
+
+    /// 本地来源优先于缓存与网络来源，且结果不写入缓存
+    fn is_local(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,48 +58,159 @@ pub struct LyricsItem {
     pub title: String,
     pub artist: String,
     pub album: String,
+    /// 歌曲时长，单位：秒
+    pub duration: Option<f64>,
     pub params: Vec<(String, String)>,
 }
 
+/// 下载的歌词内容
+#[derive(Debug, Clone, Default)]
+pub struct LyricsContent {
+    /// 原文歌词
+    pub lyric: String,
+    /// 翻译歌词
+    pub translation: Option<String>,
+    /// 音译歌词 (罗马音/拼音)
+    pub romanization: Option<String>,
+}
+
+impl LyricsContent {
+    pub fn new(lyric: String) -> Self {
+        Self {
+            lyric,
+            ..Default::default()
+        }
+    }
+}
+
+/// 各来源的候选歌词
+struct Candidate {
+    source: &'static str,
+    item: LyricsItem,
+    content: LyricsContent,
+    score: Score,
+}
+
 // 公共基础结构
 struct BaseFetcher {
     client: reqwest::Client,
+    /// 搜索结果评分
+    scorer: Scorer,
     retries: u8,
+    /// 替换默认地址的协议与域名
+    base_url: Option<String>,
+    /// 配置的请求头，覆盖请求中的同名请求头
+    headers: HeaderMap,
 }
 
 impl Default for BaseFetcher {
     fn default() -> Self {
-        Self::new()
+        Self::new(&SourceSettings::default(), Scorer::default())
     }
 }
 
 impl BaseFetcher {
-    fn new() -> Self {
+    fn new(settings: &SourceSettings, scorer: Scorer) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout))
+            .build()
+            .unwrap_or_default();
+
+        let mut headers = HeaderMap::new();
+        let user_agent = settings
+            .user_agent
+            .iter()
+            .map(|ua| ("User-Agent", ua.as_str()));
+        let extra = settings
+            .headers
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()));
+        for (key, value) in extra.chain(user_agent) {
+            match (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(key), Ok(value)) => {
+                    headers.insert(key, value);
+                }
+                _ => log::warn!("Invalid header: {}: {}", key, value),
+            }
+        }
+
         Self {
-            client: reqwest::Client::new(),
-            retries: 3,
+            client,
+            scorer,
+            retries: settings.retries,
+            base_url: settings.base_url.clone(),
+            headers,
         }
     }
 
+    /// 配置了 base_url 时替换默认地址的协议与域名，保留路径
+    fn url(&self, url: &str) -> String {
+        let Some(base) = &self.base_url else {
+            return url.to_string();
+        };
+        let host_start = url.find("://").map_or(0, |i| i + 3);
+        let path = url[host_start..]
+            .find('/')
+            .map_or("", |i| &url[host_start + i..]);
+        format!("{}{}", base.trim_end_matches('/'), path)
+    }
+
     // 添加重试机制
     async fn fetch_with_retry<T: DeserializeOwned>(
         &self,
         request: RequestBuilder,
     ) -> Result<T, LyricsError> {
+        let res = self.send_with_retry(request).await?;
+        Ok(res.json::<T>().await?)
+    }
+
+    // 获取文本内容
+    async fn fetch_text_with_retry(&self, request: RequestBuilder) -> Result<String, LyricsError> {
+        let res = self.send_with_retry(request).await?;
+        Ok(res.text().await?)
+    }
+
+    async fn send_with_retry(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<reqwest::Response, LyricsError> {
         let mut attempt = 0;
         loop {
-            let response = request.try_clone().unwrap().send().await;
+            let response = request
+                .try_clone()
+                .unwrap()
+                .headers(self.headers.clone())
+                .send()
+                .await;
             log::debug!("REQUEST: {:?} \n RESPONSE: {:?}", request, response);
             match response {
-                Ok(res) => return Ok(res.json::<T>().await?),
+                // 服务端错误同样重试
+                Ok(res) if res.status().is_server_error() && attempt < self.retries => {
+                    log::warn!("Server error {}, retrying", res.status());
+                    tokio::time::sleep(Self::backoff(attempt)).await;
+                    attempt += 1;
+                }
+                // 重试后仍是服务端错误时不再解析错误页
+                Ok(res) if res.status().is_server_error() => {
+                    return Err(res.error_for_status().unwrap_err().into());
+                }
+                Ok(res) => return Ok(res),
                 Err(_e) if attempt < self.retries => {
-                    tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+                    tokio::time::sleep(Self::backoff(attempt)).await;
                     attempt += 1;
                 }
                 Err(e) => return Err(e.into()),
             }
         }
     }
+
+    /// 重试间隔，指数增长，最长 64 秒
+    fn backoff(attempt: u8) -> Duration {
+        Duration::from_secs(1 << attempt.min(6))
+    }
 }
 
 /// 初始client
@@ -85,64 +222,204 @@ pub fn get_lyrics_client() -> &'static LyricsClient {
 // 统一调用入口
 pub struct LyricsClient {
     fetchers: Vec<Box<dyn LyricsFetcher>>,
+    /// 并发获取的截止时间，单位：秒
+    deadline: u64,
+    /// 所有来源都没有歌词时的缓存时间，单位：秒
+    not_found_ttl: u64,
+    /// 选中网络歌词后写回音频文件标签
+    write_tags: bool,
+    scorer: Scorer,
     pub cache: CacheManager,
 }
 
 impl LyricsClient {
     fn new() -> Self {
-        let mut fetchers: Vec<Box<dyn LyricsFetcher>> = Vec::new();
-
         let config = &get_config().read().unwrap().sources;
+        Self::with_config(config, CacheManager::new())
+    }
 
-        if config.netease {
-            fetchers.push(Box::new(NeteaseFetcher::default()));
-        }
-        if config.qq {
-            fetchers.push(Box::new(QQMusicFetcher::default()));
-        }
-        if config.kugou {
-            fetchers.push(Box::new(KugouFetcher::default()));
+    /// 按指定的来源配置与缓存创建
+    pub fn with_config(config: &Sources, cache: CacheManager) -> Self {
+        let mut fetchers: Vec<Box<dyn LyricsFetcher>> = Vec::new();
+        let scorer = Scorer::new(config);
+
+        for name in &config.order {
+            let Some(settings) = config.get(name) else {
+                log::warn!("Unknown source: {}", name);
+                continue;
+            };
+            if !settings.enabled {
+                continue;
+            }
+            let fetcher: Box<dyn LyricsFetcher> = match name.as_str() {
+                "local" => Box::new(LocalFetcher::new(settings)),
+                "embedded" => Box::new(EmbeddedFetcher),
+                "netease" => Box::new(NeteaseFetcher::new(settings, scorer)),
+                "qq" => Box::new(QQMusicFetcher::new(settings, scorer)),
+                "kugou" => Box::new(KugouFetcher::new(settings, scorer)),
+                "lrclib" => Box::new(LrclibFetcher::new(settings, scorer)),
+                "ovh" => Box::new(OvhFetcher::new(settings, scorer)),
+                "command" => match CommandFetcher::new(settings, scorer) {
+                    Some(fetcher) => Box::new(fetcher),
+                    None => {
+                        log::warn!("Source command has no command configured");
+                        continue;
+                    }
+                },
+                _ => continue,
+            };
+            if fetchers
+                .iter()
+                .any(|f| f.source_name() == fetcher.source_name())
+            {
+                continue;
+            }
+            fetchers.push(fetcher);
         }
 
         Self {
             fetchers,
-            cache: CacheManager::new(),
+            deadline: config.deadline,
+            not_found_ttl: config.not_found_ttl,
+            write_tags: config.embedded.write,
+            scorer,
+            cache,
         }
     }
 
     pub async fn get_search(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
         let mut list = Vec::new();
+        let mut error = None;
 
+        // 单个来源失败不影响其他来源，全部没有结果时返回最后的错误
         for fetcher in &self.fetchers {
-            let mut sl = fetcher.search_lyric(song).await?;
-            list.append(&mut sl);
+            match fetcher.search_lyric(song).await {
+                Ok(mut sl) => list.append(&mut sl),
+                Err(e) => {
+                    log::warn!("{} search failed: {}", fetcher.source_name(), e);
+                    error = Some(e);
+                }
+            }
         }
 
-        Ok(list)
+        match error {
+            Some(e) if list.is_empty() => Err(e),
+            _ => Ok(list),
+        }
     }
 
-    pub async fn get_lyrics(&self, song: &SongInfo) -> Result<String, LyricsError> {
+    pub async fn get_lyrics(&self, song: &SongInfo) -> Result<LyricsContent, LyricsError> {
+        for fetcher in self.fetchers.iter().filter(|f| f.is_local()) {
+            match fetcher.fetch_lyric(song).await {
+                Ok((item, lyric)) => {
+                    log::info!("Local lyric for {}: {:?}", song.title, item.params);
+                    return Ok(lyric);
+                }
+                Err(e) => log::debug!("{} failed: {}", fetcher.source_name(), e),
+            }
+        }
+
         if let Some(cached) = self.cache.get(song).await {
             log::debug!("Cache lyric for: {} - {}", song.artist, song.title);
             return Ok(cached);
         }
 
-        for fetcher in &self.fetchers {
-            log::info!("Trying source: {}", fetcher.source_name());
-            match fetcher.fetch_lyric(song).await {
-                Ok(lyric) => {
-                    //if self.validate_lyric(song, &lyric) {
-                    log::info!("Successfully fetched from {}", fetcher.source_name());
-                    self.cache
-                        .store(song, fetcher.source_name(), &lyric)
-                        .await?;
-                    return Ok(lyric);
-                    // }
-                }
-                Err(e) => log::warn!("{} failed: {}", fetcher.source_name(), e),
+        if self.cache.is_not_found(song, self.not_found_ttl).await {
+            log::debug!("Cached no lyrics for: {} - {}", song.artist, song.title);
+            return Err(LyricsError::NoLyricsCached);
+        }
+
+        self.fetch_remote(song).await
+    }
+
+    /// 跳过本地来源与缓存，重新从网络来源获取并更新缓存
+    pub async fn refetch_lyrics(&self, song: &SongInfo) -> Result<LyricsContent, LyricsError> {
+        self.fetch_remote(song).await
+    }
+
+    /// 并发获取所有网络来源，缓存评分最高的歌词
+    async fn fetch_remote(&self, song: &SongInfo) -> Result<LyricsContent, LyricsError> {
+        let deadline = Instant::now() + Duration::from_secs(self.deadline);
+        let remote: Vec<_> = self.fetchers.iter().filter(|f| !f.is_local()).collect();
+        let tasks = remote.iter().enumerate().map(|(i, fetcher)| {
+            let priority = 1.0 - i as f64 / remote.len() as f64;
+            self.fetch_candidate(fetcher.as_ref(), song, deadline, priority)
+        });
+        let results = futures::future::join_all(tasks).await;
+
+        // 所有来源都明确没有歌词时才记录，超时或网络错误时下次重试
+        let not_found = !results.is_empty()
+            && results.iter().all(|result| {
+                matches!(
+                    result,
+                    Err(LyricsError::NoLyricsFound | LyricsError::EmptyLyrics)
+                )
+            });
+        let mut candidates: Vec<Candidate> = results.into_iter().flatten().collect();
+
+        for candidate in &candidates {
+            log::info!(
+                "Candidate {}: {} - {} [{}] {}",
+                candidate.source,
+                candidate.item.artist,
+                candidate.item.title,
+                candidate.item.album,
+                candidate.score
+            );
+        }
+
+        candidates.sort_by(|a, b| b.score.total().total_cmp(&a.score.total()));
+        let Some(best) = candidates.into_iter().next() else {
+            if not_found && self.not_found_ttl > 0 {
+                self.cache.store_not_found(song).await?;
+            }
+            return Err(LyricsError::NoLyricsFound);
+        };
+
+        log::info!("Best match from {}", best.source);
+        self.cache
+            .store(song, &best.item, Some(best.score.total()), &best.content)
+            .await?;
+        self.write_back(song, &best.content).await;
+        Ok(best.content)
+    }
+
+    /// 在截止时间前从单个来源获取歌词并评分
+    async fn fetch_candidate(
+        &self,
+        fetcher: &dyn LyricsFetcher,
+        song: &SongInfo,
+        deadline: Instant,
+        priority: f64,
+    ) -> Result<Candidate, LyricsError> {
+        let source = fetcher.source_name();
+        log::info!("Trying source: {}", source);
+
+        let (item, content) = match timeout_at(deadline, fetcher.fetch_lyric(song)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                log::warn!("{} failed: {}", source, e);
+                return Err(e);
+            }
+            Err(_) => {
+                log::warn!("{} timed out", source);
+                return Err(anyhow::anyhow!("{} timed out", source).into());
             }
+        };
+
+        let mut score = self.scorer.score(song, &item);
+        if let Err(e) = score.apply_content(song, &content).await {
+            log::warn!("{} returned invalid lyrics: {}", source, e);
+            return Err(e);
         }
-        Err(LyricsError::NoLyricsFound)
+        score.priority = priority;
+
+        Ok(Candidate {
+            source,
+            item,
+            content,
+            score,
+        })
     }
 
     pub async fn download(&self, song: &SongInfo, item: &LyricsItem) -> Result<(), LyricsError> {
@@ -151,9 +428,11 @@ impl LyricsClient {
                 match fetcher.download_lyric(item).await {
                     Ok(lyric) => {
                         log::info!("Successfully fetched from {}", fetcher.source_name());
-                        self.cache
-                            .store(song, fetcher.source_name(), &lyric)
-                            .await?;
+                        // 本地来源每次直接读取，不写入缓存，也不写回音频文件
+                        if !fetcher.is_local() {
+                            self.cache.store(song, item, None, &lyric).await?;
+                            self.write_back(song, &lyric).await;
+                        }
                         return Ok(());
                     }
                     Err(e) => log::warn!("{} failed: {}", fetcher.source_name(), e),
@@ -164,6 +443,38 @@ impl LyricsClient {
         Err(LyricsError::NoLyricsFound)
     }
 
+    /// 将歌词写入正在播放的本地音频文件的标签
+    pub async fn embed(&self, song: &SongInfo, content: &LyricsContent) -> Result<(), LyricsError> {
+        let path = local::audio_path(song)
+            .filter(|path| path.is_file())
+            .ok_or_else(|| anyhow::anyhow!("not a local file: {}", song.url))?;
+        if tokio::fs::metadata(&path).await?.permissions().readonly() {
+            return Err(anyhow::anyhow!("read-only file: {}", path.display()).into());
+        }
+
+        // USLT 只写入文本，时间由 SYLT 保存
+        let (text, sylt) = match LyricParser::parse(content.lyric.clone(), song.duration).await {
+            Ok(doc) => (doc.to_text(), SyltParser::encode(&doc)),
+            Err(_) => (content.lyric.clone(), Vec::new()),
+        };
+        let lyric = content.lyric.clone();
+        tokio::task::spawn_blocking(move || embedded::write_tags(&path, &lyric, &text, sylt))
+            .await
+            .map_err(anyhow::Error::from)??;
+        log::info!("Embedded lyric into {}", song.url);
+        Ok(())
+    }
+
+    /// 配置了写回时写入标签，失败只记录日志
+    async fn write_back(&self, song: &SongInfo, content: &LyricsContent) {
+        if !self.write_tags {
+            return;
+        }
+        if let Err(e) = self.embed(song, content).await {
+            log::warn!("Embed lyric failed: {}", e);
+        }
+    }
+
     #[allow(dead_code)]
     fn validate_lyric(&self, song: &SongInfo, lyric: &str) -> bool {
         let normalized_lyric = normalize_text(lyric);
@@ -177,58 +488,27 @@ impl LyricsClient {
     }
 }
 
-// 检测两者是否类似
-
-pub fn get_first(list: Vec<LyricsItem>, song: &SongInfo) -> Result<LyricsItem, LyricsError> {
-    let list: Vec<LyricsItem> = list
-        .into_iter()
-        .filter(|s| s.title == song.title || s.title.contains(&song.title))
-        .collect();
-
-    if list.is_empty() {
-        return Err(LyricsError::NoLyricsFound);
-    }
-
-    let list: Vec<LyricsItem> = if !song.artist.is_empty() {
-        list.into_iter()
-            .filter(|s| {
-                if song.artist.is_empty() {
-                    true
-                } else {
-                    let a = s.artist.to_lowercase();
-                    let b = song.artist.to_lowercase();
-                    a == b || a.contains(&b)
-                }
-            })
-            .collect()
-    } else {
-        list
-    };
-
-    if list.is_empty() {
-        return Err(LyricsError::NoLyricsFound);
-    }
-
-    let list: Vec<LyricsItem> = if !song.album.is_empty() {
-        list.into_iter()
-            .filter(|s| {
-                if song.album.is_empty() {
-                    true
-                } else {
-                    let a = s.album.to_lowercase();
-                    let b = song.album.to_lowercase();
-                    a == b || a.contains(&b)
-                }
-            })
-            .collect()
-    } else {
-        list
-    };
-
-    if list.is_empty() {
-        return Err(LyricsError::NoLyricsFound);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_capped() {
+        let cases = [
+            (0, 1),
+            (1, 2),
+            (5, 32),
+            (6, 64),
+            (7, 64),
+            (64, 64),
+            (u8::MAX, 64),
+        ];
+        for (attempt, secs) in cases {
+            assert_eq!(
+                BaseFetcher::backoff(attempt),
+                Duration::from_secs(secs),
+                "{attempt}"
+            );
+        }
     }
-
-    let first = list.first().ok_or(LyricsError::NoLyricsFound)?;
-    Ok(first.clone())
 }
@@ -0,0 +1,242 @@
+use std::{process::Stdio, time::Duration};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use super::{LyricsContent, LyricsFetcher, LyricsItem, score::Scorer};
+use crate::{config::SourceSettings, error::LyricsError, song::SongInfo};
+
+/// 传给命令的请求，同时以 JSON 写入 stdin 和以 `LYRICS_*` 环境变量传递
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    /// search 或 download
+    action: &'a str,
+    /// download 时为候选的 id
+    id: &'a str,
+    title: &'a str,
+    artist: &'a str,
+    album: &'a str,
+    /// 时长，单位：秒
+    duration: f64,
+}
+
+/// search 输出的候选
+#[derive(Debug, Deserialize)]
+struct Candidate {
+    #[serde(default)]
+    id: String,
+    title: String,
+    #[serde(default)]
+    artist: String,
+    #[serde(default)]
+    album: String,
+    #[serde(default)]
+    duration: Option<f64>,
+    /// 候选直接带上歌词时不再执行 download
+    #[serde(default)]
+    lyric: Option<String>,
+}
+
+/// download 输出的 JSON 歌词
+#[derive(Debug, Deserialize)]
+struct Lyrics {
+    lyric: String,
+    #[serde(default)]
+    translation: Option<String>,
+    #[serde(default)]
+    romanization: Option<String>,
+}
+
+/// 命令的输出，JSON 或 LRC 文本
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Output {
+    Candidates(Vec<Candidate>),
+    Lyrics(Lyrics),
+    #[serde(skip)]
+    Text(String),
+}
+
+impl Output {
+    fn parse(stdout: String) -> Self {
+        serde_json::from_str(&stdout).unwrap_or(Self::Text(stdout))
+    }
+}
+
+// 外部命令实现
+pub(super) struct CommandFetcher {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    scorer: Scorer,
+}
+
+impl CommandFetcher {
+    /// 未配置 command 时返回 None
+    pub(super) fn new(settings: &SourceSettings, scorer: Scorer) -> Option<Self> {
+        Some(Self {
+            program: settings.command.clone()?,
+            args: settings.args.clone(),
+            timeout: Duration::from_secs(settings.timeout),
+            scorer,
+        })
+    }
+
+    async fn run(&self, request: &Request<'_>) -> Result<Output, LyricsError> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env("LYRICS_ACTION", request.action)
+            .env("LYRICS_ID", request.id)
+            .env("LYRICS_TITLE", request.title)
+            .env("LYRICS_ARTIST", request.artist)
+            .env("LYRICS_ALBUM", request.album)
+            .env("LYRICS_DURATION", request.duration.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // 命令可能不读取 stdin，写入失败时忽略
+        if let Some(mut stdin) = child.stdin.take() {
+            let input = serde_json::to_vec(request).map_err(anyhow::Error::from)?;
+            let _ = stdin.write_all(&input).await;
+        }
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow::anyhow!("command timed out: {}", self.program))??;
+
+        // 非零退出是命令出错，不代表没有歌词，不能记录为未找到
+        if !output.status.success() {
+            return Err(LyricsError::CommandFailed {
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        // 正常退出且没有输出时才视为没有歌词
+        let stdout =
+            String::from_utf8(output.stdout).map_err(|_| LyricsError::LyricsDecodeError)?;
+        if stdout.trim().is_empty() {
+            return Err(LyricsError::NoLyricsFound);
+        }
+        Ok(Output::parse(stdout))
+    }
+
+    fn request<'a>(action: &'a str, song: &'a SongInfo) -> Request<'a> {
+        Request {
+            action,
+            id: "",
+            title: &song.title,
+            artist: &song.artist,
+            album: &song.album,
+            duration: song.duration,
+        }
+    }
+
+    fn item(&self, candidate: Candidate) -> LyricsItem {
+        let mut params = vec![("id".to_string(), candidate.id)];
+        if let Some(lyric) = candidate.lyric {
+            params.push(("lyric".to_string(), lyric));
+        }
+        LyricsItem {
+            source: self.source_name().into(),
+            title: candidate.title,
+            artist: candidate.artist,
+            album: candidate.album,
+            duration: candidate.duration,
+            params,
+        }
+    }
+}
+
+#[async_trait]
+impl LyricsFetcher for CommandFetcher {
+    async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
+        let lyrics = match self.run(&Self::request("search", song)).await? {
+            Output::Candidates(candidates) => {
+                let list: Vec<LyricsItem> = candidates.into_iter().map(|c| self.item(c)).collect();
+                log::debug!("Get List: {:?}", list);
+                return match list.is_empty() {
+                    true => Err(LyricsError::NoLyricsFound),
+                    false => Ok(list),
+                };
+            }
+            Output::Lyrics(lyrics) => lyrics,
+            Output::Text(lyric) => Lyrics {
+                lyric,
+                translation: None,
+                romanization: None,
+            },
+        };
+
+        // 直接输出歌词时视为与歌曲信息一致的唯一候选
+        let tracks = [
+            ("lyric", Some(lyrics.lyric)),
+            ("translation", lyrics.translation),
+            ("romanization", lyrics.romanization),
+        ];
+        Ok(vec![LyricsItem {
+            source: self.source_name().into(),
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            duration: None,
+            params: tracks
+                .into_iter()
+                .filter_map(|(k, v)| Some((k.to_string(), v?)))
+                .collect(),
+        }])
+    }
+
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let param = |key: &str| {
+            item.params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        if let Some(lyric) = param("lyric") {
+            return Ok(LyricsContent {
+                lyric: lyric.to_string(),
+                translation: param("translation").map(String::from),
+                romanization: param("romanization").map(String::from),
+            });
+        }
+
+        let request = Request {
+            action: "download",
+            id: param("id").unwrap_or_default(),
+            title: &item.title,
+            artist: &item.artist,
+            album: &item.album,
+            duration: item.duration.unwrap_or_default(),
+        };
+        match self.run(&request).await? {
+            Output::Text(lyric) => Ok(LyricsContent::new(lyric)),
+            Output::Lyrics(lyrics) => Ok(LyricsContent {
+                lyric: lyrics.lyric,
+                translation: lyrics.translation,
+                romanization: lyrics.romanization,
+            }),
+            Output::Candidates(_) => Err(LyricsError::NoLyricsFound),
+        }
+    }
+
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
+        let list = self.search_lyric(song).await?;
+        let item = self.scorer.best_match(list, song)?;
+        log::debug!("Get song: {:?} info: {:?}", item, song);
+        let content = self.download_lyric(&item).await?;
+        Ok((item, content))
+    }
+
+    fn source_name(&self) -> &'static str {
+        "Command"
+    }
+}
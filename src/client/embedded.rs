@@ -0,0 +1,214 @@
+use std::{borrow::Cow, fs::File, path::Path};
+
+use async_trait::async_trait;
+use lofty::{
+    TextEncoding,
+    config::{ParseOptions, WriteOptions},
+    file::FileType,
+    flac::FlacFile,
+    id3::v2::{
+        BinaryFrame, Frame, FrameId, Id3v2Tag, SyncTextContentType, SynchronizedTextFrame,
+        TimestampFormat, UnsynchronizedTextFrame,
+    },
+    mpeg::MpegFile,
+    ogg::{OpusFile, VorbisFile, tag::VorbisComments},
+    prelude::*,
+    probe::Probe,
+};
+
+use super::{LyricsContent, LyricsFetcher, LyricsItem, local::audio_path};
+use crate::{
+    error::LyricsError,
+    song::{SongInfo, SyltParser},
+};
+
+// 内嵌歌词实现，读取音频文件标签中的歌词
+pub(super) struct EmbeddedFetcher;
+
+impl EmbeddedFetcher {
+    /// 在阻塞线程中读取标签
+    async fn read(path: &Path) -> Result<LyricsContent, LyricsError> {
+        let path = path.to_path_buf();
+        let lyric = tokio::task::spawn_blocking(move || read_tags(&path))
+            .await
+            .map_err(anyhow::Error::from)??;
+        Ok(LyricsContent::new(lyric))
+    }
+
+    fn item(&self, song: &SongInfo, path: &Path) -> LyricsItem {
+        LyricsItem {
+            source: self.source_name().into(),
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            duration: None,
+            params: vec![("path".to_string(), path.to_string_lossy().into_owned())],
+        }
+    }
+}
+
+#[async_trait]
+impl LyricsFetcher for EmbeddedFetcher {
+    async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
+        let (item, _) = self.fetch_lyric(song).await?;
+        Ok(vec![item])
+    }
+
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let path = item
+            .params
+            .iter()
+            .find(|(k, _)| k == "path")
+            .map(|(_, v)| v.as_str())
+            .ok_or(LyricsError::NoLyricsFound)?;
+        Self::read(Path::new(path)).await
+    }
+
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
+        let path = audio_path(song)
+            .filter(|path| path.is_file())
+            .ok_or(LyricsError::NoLyricsFound)?;
+        let content = Self::read(&path).await?;
+        Ok((self.item(song, &path), content))
+    }
+
+    fn source_name(&self) -> &'static str {
+        "Embedded"
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+/// 依次读取 ID3v2 SYLT、USLT，Vorbis LYRICS、UNSYNCEDLYRICS 与 MP4 ©lyr
+fn read_tags(path: &Path) -> Result<String, LyricsError> {
+    let tagged = lofty::read_from_path(path)?;
+
+    // SYLT 不会转换为通用标签，需要直接读取 ID3v2
+    if tagged.file_type() == FileType::Mpeg {
+        let mpeg = MpegFile::read_from(
+            &mut File::open(path)?,
+            ParseOptions::new().read_properties(false),
+        )?;
+        let duration = tagged.properties().duration().as_secs_f64();
+        if let Some(lyric) = mpeg.id3v2().and_then(|tag| synced_lyric(tag, duration)) {
+            return Ok(lyric);
+        }
+    }
+
+    tagged
+        .tags()
+        .iter()
+        .flat_map(|tag| {
+            [ItemKey::Lyrics, ItemKey::UnsyncLyrics]
+                .into_iter()
+                .filter_map(|key| tag.get_string(key))
+        })
+        .find(|lyric| !lyric.trim().is_empty())
+        .map(|lyric| lyric.trim_start_matches('\u{feff}').to_string())
+        .ok_or(LyricsError::NoLyricsFound)
+}
+
+/// SYLT 转换为 LRC，只支持毫秒时间，歌词类型优先，最后一行持续到音频结束
+fn synced_lyric(tag: &Id3v2Tag, duration: f64) -> Option<String> {
+    let mut frames: Vec<SynchronizedTextFrame> = tag
+        .into_iter()
+        .filter_map(|frame| match frame {
+            Frame::Binary(binary) if frame.id().as_str() == "SYLT" => {
+                SynchronizedTextFrame::parse(&binary.data, frame.flags())
+                    .inspect_err(|e| log::warn!("Invalid SYLT frame: {}", e))
+                    .ok()
+            }
+            _ => None,
+        })
+        .filter(|sylt| sylt.timestamp_format == TimestampFormat::MS)
+        .collect();
+    frames.sort_by_key(|sylt| sylt.content_type != SyncTextContentType::Lyrics);
+
+    frames
+        .iter()
+        .find_map(|sylt| SyltParser::parse(&sylt.content, duration).ok())
+        .map(|doc| doc.to_lrc())
+}
+
+/// 写入歌词：MP3 写入不带时间的 USLT 与 SYLT，FLAC/Ogg 写入 LRC 格式的 LYRICS，
+/// 删除已有的歌词，包括 UNSYNCEDLYRICS 等其他写法，避免读取到不一致的歌词
+pub(super) fn write_tags(
+    path: &Path,
+    lyric: &str,
+    text: &str,
+    sylt: Vec<(u32, String)>,
+) -> Result<(), LyricsError> {
+    let options = ParseOptions::new().read_properties(false);
+    let file_type = Probe::open(path)?.guess_file_type()?.file_type();
+    let mut file = File::open(path)?;
+
+    let comments = |tag: Option<&VorbisComments>| {
+        let mut tag = tag.cloned().unwrap_or_default();
+        for key in LYRICS_KEYS {
+            tag.remove(key).for_each(drop);
+        }
+        tag.insert("LYRICS".to_string(), lyric.to_string());
+        tag
+    };
+    match file_type {
+        Some(FileType::Mpeg) => {
+            let mpeg = MpegFile::read_from(&mut file, options)?;
+            let mut tag = mpeg.id3v2().cloned().unwrap_or_default();
+            tag.retain(|frame| match frame {
+                Frame::UserText(frame) => !LYRICS_KEYS
+                    .iter()
+                    .any(|key| frame.description.eq_ignore_ascii_case(key)),
+                _ => !matches!(frame.id().as_str(), "USLT" | "SYLT"),
+            });
+            tag.insert(Frame::UnsynchronizedText(UnsynchronizedTextFrame::new(
+                TextEncoding::UTF8,
+                *b"XXX",
+                String::new(),
+                text.to_string(),
+            )));
+            if !sylt.is_empty() {
+                tag.insert(sylt_frame(sylt)?);
+            }
+            tag.save_to_path(path, WriteOptions::default())?;
+        }
+        Some(FileType::Flac) => {
+            let flac = FlacFile::read_from(&mut file, options)?;
+            comments(flac.vorbis_comments()).save_to_path(path, WriteOptions::default())?;
+        }
+        Some(FileType::Vorbis) => {
+            let vorbis = VorbisFile::read_from(&mut file, options)?;
+            comments(Some(vorbis.vorbis_comments())).save_to_path(path, WriteOptions::default())?;
+        }
+        Some(FileType::Opus) => {
+            let opus = OpusFile::read_from(&mut file, options)?;
+            comments(Some(opus.vorbis_comments())).save_to_path(path, WriteOptions::default())?;
+        }
+        _ => return Err(anyhow::anyhow!("unsupported audio file: {}", path.display()).into()),
+    }
+    Ok(())
+}
+
+/// Vorbis 注释与 ID3v2 TXXX 中常见的歌词字段
+const LYRICS_KEYS: [&str; 2] = ["LYRICS", "UNSYNCEDLYRICS"];
+
+fn sylt_frame(content: Vec<(u32, String)>) -> Result<Frame<'static>, LyricsError> {
+    let data = SynchronizedTextFrame::new(
+        TextEncoding::UTF8,
+        *b"XXX",
+        TimestampFormat::MS,
+        SyncTextContentType::Lyrics,
+        None,
+        content,
+    )
+    .as_bytes(WriteOptions::default())
+    .map_err(anyhow::Error::from)?;
+    Ok(Frame::Binary(BinaryFrame::new(
+        FrameId::Valid(Cow::Borrowed("SYLT")),
+        data,
+    )))
+}
@@ -1,9 +1,24 @@
+use std::io::Read;
+
 use async_trait::async_trait;
 use base64::{Engine, prelude::BASE64_STANDARD};
+use flate2::read::ZlibDecoder;
 use serde::Deserialize;
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{client::get_first, error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricsContent, LyricsFetcher, LyricsItem, score::Scorer};
+use crate::{
+    config::SourceSettings,
+    error::LyricsError,
+    song::{KrcParser, SongInfo},
+};
+
+/// KRC 文件头
+const KRC_HEADER: &[u8] = b"krc1";
+
+/// KRC 异或密钥
+const KRC_KEY: [u8; 16] = [
+    0x40, 0x47, 0x61, 0x77, 0x5e, 0x32, 0x74, 0x47, 0x51, 0x36, 0x31, 0x2d, 0xce, 0xd2, 0x6e, 0x69,
+];
 
 #[derive(Debug, Deserialize)]
 struct SearchResponse {
@@ -24,6 +39,8 @@ struct Song {
     singername: String,
     // songname: String,
     songname_original: String,
+    /// 时长，单位：秒
+    duration: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,20 +54,30 @@ struct Candidate {
     download_id: String,
     singer: String,
     song: String,
+    /// 时长，单位：毫秒
+    duration: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct LyricData {
     content: String,
+    /// 返回的格式 krc 或 lrc
+    #[serde(default)]
+    fmt: String,
 }
 
 // Kugou音乐实现
-#[derive(Default)]
 pub(super) struct KugouFetcher {
     base: BaseFetcher,
 }
 
 impl KugouFetcher {
+    pub(super) fn new(settings: &SourceSettings, scorer: Scorer) -> Self {
+        Self {
+            base: BaseFetcher::new(settings, scorer),
+        }
+    }
+
     // 酷狗歌词解密函数
     fn decode_lyric(&self, encrypted: &str) -> Result<String, LyricsError> {
         let bytes = BASE64_STANDARD.decode(encrypted)?;
@@ -60,6 +87,31 @@ impl KugouFetcher {
         }
         Ok(re)
     }
+
+    // KRC 解密：base64 解码，去掉文件头后异或，再 zlib 解压
+    fn decode_krc(&self, encrypted: &str) -> Result<String, LyricsError> {
+        let bytes = BASE64_STANDARD.decode(encrypted)?;
+        let data = bytes
+            .strip_prefix(KRC_HEADER)
+            .ok_or(LyricsError::LyricsDecodeError)?;
+
+        let xored: Vec<u8> = data
+            .iter()
+            .enumerate()
+            .map(|(i, b)| b ^ KRC_KEY[i % KRC_KEY.len()])
+            .collect();
+
+        let mut decoded = String::new();
+        ZlibDecoder::new(xored.as_slice())
+            .read_to_string(&mut decoded)
+            .map_err(|_| LyricsError::LyricsDecodeError)?;
+
+        let decoded = decoded.trim_start_matches('\u{feff}').to_string();
+        if decoded.is_empty() {
+            return Err(LyricsError::NoLyricsFound);
+        }
+        Ok(decoded)
+    }
 }
 
 #[async_trait]
@@ -67,58 +119,57 @@ impl LyricsFetcher for KugouFetcher {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
         // 1. 搜索歌曲
         let search_url = "http://mobilecdn.kugou.com/api/v3/search/song";
-        let request = self.base.client.get(search_url).query(&[
+        let request = self.base.client.get(self.base.url(search_url)).query(&[
             (
                 "keyword",
                 format!("{} {}", song.title, song.artist).as_str(),
             ),
             ("page", "1"),
-            ("pagesize", "1"),
+            ("pagesize", "10"),
         ]);
 
         let data: SearchResponse = self.base.fetch_with_retry(request).await?;
         log::debug!("song json: {:?}", data);
 
-        let search = data
+        // 按匹配度选出歌曲，hash 与 album_id 暂存在 params 中
+        let songs = data
             .data
             .info
             .into_iter()
-            .filter(|s| {
-                s.songname_original == song.title || s.songname_original.contains(&song.title)
-            })
-            .filter(|s| {
-                if song.artist.is_empty() {
-                    true
-                } else {
-                    let a = s.singername.to_lowercase();
-                    let b = song.artist.to_lowercase();
-                    a == b || a.contains(&b)
-                }
+            .map(|s| LyricsItem {
+                source: self.source_name().into(),
+                title: s.songname_original,
+                artist: s.singername.replace('、', "/"),
+                album: s.album_name,
+                duration: s.duration.map(|d| d as f64),
+                params: vec![
+                    ("hash".to_string(), s.hash),
+                    ("album_id".to_string(), s.album_id),
+                ],
             })
-            .filter(|s| {
-                if song.album.is_empty() {
-                    true
-                } else {
-                    let a = s.album_name.to_lowercase();
-                    let b = song.album.to_lowercase();
-                    a == b || a.contains(&b)
-                }
-            })
-            .next()
-            .map(|s| s)
-            .ok_or(LyricsError::NoLyricsFound)?;
+            .collect();
+        let search = self.base.scorer.best_match(songs, song)?;
+        let param = |key: &str| {
+            search
+                .params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .unwrap_or_default()
+        };
+        let (hash, album_id) = (param("hash"), param("album_id"));
 
-        log::debug!("song hash: {} {}", search.album_id, search.hash);
+        log::debug!("song hash: {} {}", album_id, hash);
 
         // 2. 获取歌词
         let lyric_url = "http://krcs.kugou.com/search";
         let request = self
             .base
             .client
-            .get(lyric_url)
+            .get(self.base.url(lyric_url))
             .query(&[
-                ("hash", search.hash.as_str()),
-                ("album_id", search.album_id.as_str()),
+                ("hash", hash),
+                ("album_id", album_id),
                 ("ver", "1"),
                 ("client", "pc"),
                 ("man", "yes"),
@@ -135,7 +186,8 @@ impl LyricsFetcher for KugouFetcher {
                 let source = self.source_name().into();
                 let title = s.song;
                 let artist = s.singer;
-                let album = search.album_name.clone();
+                let album = search.album.clone();
+                let duration = s.duration.map(|d| d as f64 / 1000.0).or(search.duration);
                 let params = vec![
                     ("accesskey".to_string(), s.accesskey),
                     ("id".to_string(), s.download_id),
@@ -146,6 +198,7 @@ impl LyricsFetcher for KugouFetcher {
                     title,
                     artist,
                     album,
+                    duration,
                     params,
                 }
             })
@@ -160,12 +213,12 @@ impl LyricsFetcher for KugouFetcher {
         }
     }
 
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError> {
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
         let mut params = item.params.clone();
         params.append(&mut vec![
             ("ver".to_string(), "1".to_string()),
             ("client".to_string(), "pc".to_string()),
-            ("fmt".to_string(), "lrc".to_string()),
+            ("fmt".to_string(), "krc".to_string()),
             ("charset".to_string(), "utf8".to_string()),
         ]);
 
@@ -174,26 +227,85 @@ impl LyricsFetcher for KugouFetcher {
         let request = self
             .base
             .client
-            .get(lyric_download_url)
+            .get(self.base.url(lyric_download_url))
             .query(&params)
             .header("User-Agent", "Mozilla/5.0");
 
         let data: LyricData = self.base.fetch_with_retry(request).await?;
         log::debug!("lyric: {:?}", data);
 
-        let decoded = self.decode_lyric(&data.content)?;
-        Ok(decoded)
+        if data.fmt == "lrc" {
+            let decoded = self.decode_lyric(&data.content)?;
+            return Ok(LyricsContent::new(decoded));
+        }
+
+        // KRC 转为 enhanced LRC 保存
+        let krc = self.decode_krc(&data.content)?;
+        let doc = KrcParser::parse(&krc).await?;
+        Ok(LyricsContent {
+            lyric: doc.to_lrc(),
+            translation: doc.translation_lrc(),
+            romanization: doc.romanization_lrc(),
+        })
     }
 
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
         log::debug!("kugou start ");
         let list = self.search_lyric(song).await?;
-        let item = get_first(list, song)?;
+        let item = self.base.scorer.best_match(list, song)?;
         log::debug!("Get song: {:?} info: {:?}", item, song);
-        self.download_lyric(&item).await
+        let content = self.download_lyric(&item).await?;
+        Ok((item, content))
     }
 
     fn source_name(&self) -> &'static str {
         "Kugou"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(json: &str) -> LyricData {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn fetcher() -> KugouFetcher {
+        KugouFetcher::new(&SourceSettings::default(), Scorer::default())
+    }
+
+    #[tokio::test]
+    async fn krc_is_decrypted_and_parsed() {
+        let data = fixture(include_str!("../../tests/fixtures/kugou/download_krc.json"));
+
+        let krc = fetcher().decode_krc(&data.content).unwrap();
+        assert!(krc.starts_with("[ti:晴天]"), "{krc}");
+        assert!(krc.contains("[1000,3000]<0,1000,0>从出生<1000,2000,0>那年就飘着"));
+
+        let doc = KrcParser::parse(&krc).await.unwrap();
+        assert!(
+            doc.to_lrc()
+                .contains("[00:01.00]<00:01.00>从出生<00:02.00>那年就飘着")
+        );
+        assert!(
+            doc.translation_lrc()
+                .unwrap()
+                .contains("[00:04.00]Swinging in childhood")
+        );
+    }
+
+    #[test]
+    fn corrupt_krc_fails() {
+        let data = fixture(include_str!(
+            "../../tests/fixtures/kugou/download_corrupt.json"
+        ));
+
+        let err = fetcher().decode_krc(&data.content).unwrap_err();
+        assert!(matches!(err, LyricsError::LyricsDecodeError), "{err}");
+        assert!(fetcher().decode_krc("bm90IGtyYw==").is_err());
+    }
+}
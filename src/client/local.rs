@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::Url;
+
+use super::{LyricsContent, LyricsFetcher, LyricsItem};
+use crate::{config::SourceSettings, error::LyricsError, song::SongInfo, utils::normalize_text};
+
+/// 支持的歌词文件，靠前的优先
+const EXTENSIONS: [&str; 2] = ["lrc", "txt"];
+
+/// 未配置时在歌词目录中匹配的文件名
+const DEFAULT_PATTERNS: [&str; 3] = ["{artist} - {title}", "{title} - {artist}", "{title}"];
+
+// 本地歌词实现，读取音频文件旁与歌词目录中的歌词
+pub(super) struct LocalFetcher {
+    dirs: Vec<PathBuf>,
+    patterns: Vec<String>,
+}
+
+impl LocalFetcher {
+    pub(super) fn new(settings: &SourceSettings) -> Self {
+        let patterns = match settings.patterns.is_empty() {
+            true => DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect(),
+            false => settings.patterns.clone(),
+        };
+        Self {
+            dirs: settings.dirs.iter().map(|d| expand_home(d)).collect(),
+            patterns,
+        }
+    }
+
+    /// 与音频文件同名的歌词，如 `/music/a.flac` 对应 `/music/a.lrc`
+    fn sidecars(song: &SongInfo) -> Vec<PathBuf> {
+        let Some(audio) = audio_path(song) else {
+            return Vec::new();
+        };
+
+        EXTENSIONS
+            .iter()
+            .map(|ext| audio.with_extension(ext))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// 歌词目录中文件名与匹配模式一致的歌词，按模式顺序排列
+    async fn folder_matches(&self, song: &SongInfo) -> Vec<PathBuf> {
+        let names: Vec<String> = self
+            .patterns
+            .iter()
+            .map(|p| {
+                normalize_text(
+                    &p.replace("{artist}", &song.artist)
+                        .replace("{title}", &song.title)
+                        .replace("{album}", &song.album),
+                )
+            })
+            .collect();
+
+        let mut found = Vec::new();
+        for dir in &self.dirs {
+            let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+                log::debug!("Lyrics dir unavailable: {}", dir.display());
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                let Some(ext) = EXTENSIONS.iter().position(|e| has_extension(&path, e)) else {
+                    continue;
+                };
+                let stem = path
+                    .file_stem()
+                    .map(|s| normalize_text(&s.to_string_lossy()))
+                    .unwrap_or_default();
+                if let Some(rank) = names.iter().position(|n| *n == stem) {
+                    found.push((rank, ext, path));
+                }
+            }
+        }
+
+        found.sort();
+        found.into_iter().map(|(_, _, path)| path).collect()
+    }
+}
+
+#[async_trait]
+impl LyricsFetcher for LocalFetcher {
+    async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
+        let mut paths = Self::sidecars(song);
+        paths.extend(self.folder_matches(song).await);
+
+        let list: Vec<LyricsItem> = paths
+            .into_iter()
+            .map(|path| LyricsItem {
+                source: self.source_name().into(),
+                title: song.title.clone(),
+                artist: song.artist.clone(),
+                album: song.album.clone(),
+                duration: None,
+                params: vec![("path".to_string(), path.to_string_lossy().into_owned())],
+            })
+            .collect();
+
+        log::debug!("Get List: {:?}", list);
+
+        if !list.is_empty() {
+            Ok(list)
+        } else {
+            Err(LyricsError::NoLyricsFound)
+        }
+    }
+
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let path = item
+            .params
+            .iter()
+            .find(|(k, _)| k == "path")
+            .map(|(_, v)| v.as_str())
+            .ok_or(LyricsError::NoLyricsFound)?;
+
+        let bytes = tokio::fs::read(path).await?;
+        let lyric = String::from_utf8_lossy(&bytes)
+            .trim_start_matches('\u{feff}')
+            .to_string();
+        if lyric.trim().is_empty() {
+            return Err(LyricsError::EmptyLyrics);
+        }
+        Ok(LyricsContent::new(lyric))
+    }
+
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
+        for item in self.search_lyric(song).await? {
+            match self.download_lyric(&item).await {
+                Ok(content) => return Ok((item, content)),
+                Err(e) => log::warn!("Read local lyric {:?} failed: {}", item.params, e),
+            }
+        }
+        Err(LyricsError::NoLyricsFound)
+    }
+
+    fn source_name(&self) -> &'static str {
+        "Local"
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+/// `xesam:url` 为 `file://` 时对应的本地音频文件
+pub(super) fn audio_path(song: &SongInfo) -> Option<PathBuf> {
+    Url::parse(&song.url)
+        .ok()
+        .filter(|url| url.scheme() == "file")
+        .and_then(|url| url.to_file_path().ok())
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(ext))
+}
+
+/// 展开开头的 `~`
+fn expand_home(dir: &str) -> PathBuf {
+    match dir.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(dir),
+    }
+}
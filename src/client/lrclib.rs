@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{BaseFetcher, LyricsContent, LyricsFetcher, LyricsItem, score::Scorer};
+use crate::{config::SourceSettings, error::LyricsError, song::SongInfo};
+
+/// LRCLIB 要求请求带上应用标识
+const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    " v",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+
+// data
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Record {
+    id: u64,
+    track_name: String,
+    artist_name: String,
+    #[serde(default)]
+    album_name: Option<String>,
+    /// 时长，单位：秒
+    #[serde(default)]
+    duration: Option<f64>,
+    /// 纯音乐
+    #[serde(default)]
+    instrumental: bool,
+    #[serde(default)]
+    plain_lyrics: Option<String>,
+    #[serde(default)]
+    synced_lyrics: Option<String>,
+}
+
+impl Record {
+    fn item(&self, source: &str) -> LyricsItem {
+        LyricsItem {
+            source: source.into(),
+            title: self.track_name.clone(),
+            artist: self.artist_name.clone(),
+            album: self.album_name.clone().unwrap_or_default(),
+            duration: self.duration,
+            params: vec![("id".to_string(), self.id.to_string())],
+        }
+    }
+
+    /// 优先使用带时间的歌词
+    fn into_content(self) -> Result<LyricsContent, LyricsError> {
+        if self.instrumental {
+            return Err(LyricsError::NoLyricsFound);
+        }
+        [self.synced_lyrics, self.plain_lyrics]
+            .into_iter()
+            .flatten()
+            .find(|s| !s.trim().is_empty())
+            .map(LyricsContent::new)
+            .ok_or(LyricsError::NoLyricsFound)
+    }
+}
+
+// LRCLIB 实现
+pub(super) struct LrclibFetcher {
+    base: BaseFetcher,
+}
+
+impl LrclibFetcher {
+    pub(super) fn new(settings: &SourceSettings, scorer: Scorer) -> Self {
+        Self {
+            base: BaseFetcher::new(settings, scorer),
+        }
+    }
+
+    // 按完整的歌曲信息精确获取，需要作者、专辑与时长
+    async fn get_exact(&self, song: &SongInfo) -> Result<Record, LyricsError> {
+        if song.artist.is_empty() || song.album.is_empty() || song.duration <= 0.0 {
+            return Err(LyricsError::NoLyricsFound);
+        }
+
+        let get_url = "https://lrclib.net/api/get";
+        let duration = (song.duration.round() as u64).to_string();
+        let request = self
+            .base
+            .client
+            .get(self.base.url(get_url))
+            .query(&[
+                ("track_name", song.title.as_str()),
+                ("artist_name", song.artist.as_str()),
+                ("album_name", song.album.as_str()),
+                ("duration", duration.as_str()),
+            ])
+            .header("User-Agent", USER_AGENT);
+
+        self.base.fetch_with_retry(request).await
+    }
+}
+
+#[async_trait]
+impl LyricsFetcher for LrclibFetcher {
+    async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
+        let search_url = "https://lrclib.net/api/search";
+        let mut query = vec![("track_name", song.title.as_str())];
+        if !song.artist.is_empty() {
+            query.push(("artist_name", song.artist.as_str()));
+        }
+        let request = self
+            .base
+            .client
+            .get(self.base.url(search_url))
+            .query(&query)
+            .header("User-Agent", USER_AGENT);
+
+        let data: Vec<Record> = self.base.fetch_with_retry(request).await?;
+
+        let list: Vec<LyricsItem> = data
+            .into_iter()
+            .filter(|s| !s.instrumental)
+            .map(|s| s.item(self.source_name()))
+            .collect();
+
+        log::debug!("Get List: {:?}", list);
+
+        if !list.is_empty() {
+            Ok(list)
+        } else {
+            Err(LyricsError::NoLyricsFound)
+        }
+    }
+
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let id = item
+            .params
+            .iter()
+            .find(|(k, _)| k == "id")
+            .map(|(_, v)| v.as_str())
+            .ok_or(LyricsError::NoLyricsFound)?;
+
+        let get_url = format!("https://lrclib.net/api/get/{id}");
+        let request = self
+            .base
+            .client
+            .get(self.base.url(&get_url))
+            .header("User-Agent", USER_AGENT);
+
+        let data: Record = self.base.fetch_with_retry(request).await?;
+        data.into_content()
+    }
+
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
+        // 精确匹配失败时再搜索
+        match self.get_exact(song).await {
+            Ok(record) => {
+                let item = record.item(self.source_name());
+                if let Ok(content) = record.into_content() {
+                    return Ok((item, content));
+                }
+            }
+            Err(e) => log::debug!("LRCLIB exact match failed: {e}"),
+        }
+
+        let list = self.search_lyric(song).await?;
+        let item = self.base.scorer.best_match(list, song)?;
+        log::debug!("Get song: {:?} info: {:?}", item, song);
+        let content = self.download_lyric(&item).await?;
+        Ok((item, content))
+    }
+
+    fn source_name(&self) -> &'static str {
+        "LRCLIB"
+    }
+}
@@ -1,8 +1,12 @@
 use async_trait::async_trait;
 use serde::Deserialize;
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{client::get_first, error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricsContent, LyricsFetcher, LyricsItem, score::Scorer};
+use crate::{
+    config::SourceSettings,
+    error::LyricsError,
+    song::{SongInfo, YrcParser},
+};
 
 #[derive(Debug, Deserialize)]
 struct Response {
@@ -22,7 +26,8 @@ struct Song {
     name: String,
     artists: Vec<Artist>,
     album: Album,
-    // duration: u64,
+    /// 时长，单位：毫秒
+    duration: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,27 +46,52 @@ struct Album {
 #[derive(Debug, Deserialize)]
 struct LyricData {
     lrc: LrcData,
+    /// 翻译
+    tlyric: Option<LrcData>,
+    /// 罗马音
+    romalrc: Option<LrcData>,
+    /// 逐字歌词
+    yrc: Option<LrcData>,
+    /// 与逐字歌词对齐的翻译
+    ytlrc: Option<LrcData>,
+    /// 与逐字歌词对齐的罗马音
+    yromalrc: Option<LrcData>,
 }
 
 #[derive(Debug, Deserialize)]
 struct LrcData {
+    #[serde(default)]
     lyric: String,
 }
 
+impl LrcData {
+    fn non_empty(self) -> Option<String> {
+        match self.lyric.trim().is_empty() {
+            true => None,
+            false => Some(self.lyric),
+        }
+    }
+}
+
 // 网易云音乐实现
-#[derive(Default)]
 pub(super) struct NeteaseFetcher {
     base: BaseFetcher,
 }
 
-impl NeteaseFetcher {}
+impl NeteaseFetcher {
+    pub(super) fn new(settings: &SourceSettings, scorer: Scorer) -> Self {
+        Self {
+            base: BaseFetcher::new(settings, scorer),
+        }
+    }
+}
 
 #[async_trait]
 impl LyricsFetcher for NeteaseFetcher {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
         let search_url = "https://music.163.com/api/search/get/";
 
-        let request = self.base.client.get(search_url).query(&[
+        let request = self.base.client.get(self.base.url(search_url)).query(&[
             ("s", format!("{} {}", song.title, song.artist).as_str()),
             ("type", "1"),
             ("limit", "10"), // song_id 1, album_id 10 playlist_id 1000
@@ -82,15 +112,19 @@ impl LyricsFetcher for NeteaseFetcher {
                     .iter()
                     .map(|aa| aa.name.clone())
                     .collect::<Vec<String>>()
-                    .join(" ");
+                    .join("/");
                 // .fold(String::new(), |mut full, a| {
                 //     full.push_str(&a.name);
                 //     full
                 // });
                 let album = s.album.name;
+                let duration = s.duration.map(|d| d as f64 / 1000.0);
                 let params = vec![
                     ("id".to_string(), s.id.to_string()),
                     ("lv".to_string(), "1".to_string()),
+                    ("tv".to_string(), "-1".to_string()),
+                    ("rv".to_string(), "-1".to_string()),
+                    ("yv".to_string(), "-1".to_string()),
                 ];
 
                 LyricsItem {
@@ -98,6 +132,7 @@ impl LyricsFetcher for NeteaseFetcher {
                     title,
                     artist,
                     album,
+                    duration,
                     params,
                 }
             })
@@ -112,20 +147,53 @@ impl LyricsFetcher for NeteaseFetcher {
         }
     }
 
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError> {
-        let lyric_url = "https://music.163.com/api/song/lyric";
-        let request = self.base.client.get(lyric_url).query(&item.params);
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let lyric_url = "https://music.163.com/api/song/lyric/v1";
+        let request = self
+            .base
+            .client
+            .get(self.base.url(lyric_url))
+            .query(&item.params);
         let data: LyricData = self.base.fetch_with_retry(request).await?;
         log::debug!("Get lyric: {:?}", data);
-        Ok(data.lrc.lyric)
+
+        // YRC 转为 enhanced LRC 保存，没有时使用 lrc
+        if let Some(yrc) = data.yrc.and_then(LrcData::non_empty) {
+            match YrcParser::parse(&yrc).await {
+                Ok(doc) => {
+                    return Ok(LyricsContent {
+                        lyric: doc.to_lrc(),
+                        translation: data
+                            .ytlrc
+                            .and_then(LrcData::non_empty)
+                            .or_else(|| data.tlyric.and_then(LrcData::non_empty)),
+                        romanization: data
+                            .yromalrc
+                            .and_then(LrcData::non_empty)
+                            .or_else(|| data.romalrc.and_then(LrcData::non_empty)),
+                    });
+                }
+                Err(e) => log::warn!("Parse yrc failed, fallback to lrc: {e}"),
+            }
+        }
+
+        Ok(LyricsContent {
+            lyric: data.lrc.lyric,
+            translation: data.tlyric.and_then(LrcData::non_empty),
+            romanization: data.romalrc.and_then(LrcData::non_empty),
+        })
     }
 
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
         log::debug!("Netease song: {:?}", song);
         let list = self.search_lyric(song).await?;
-        let item = get_first(list, song)?;
+        let item = self.base.scorer.best_match(list, song)?;
         log::debug!("Get song: {:?} info: {:?}", item, song);
-        self.download_lyric(&item).await
+        let content = self.download_lyric(&item).await?;
+        Ok((item, content))
     }
 
     fn source_name(&self) -> &'static str {
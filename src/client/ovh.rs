@@ -1,55 +1,175 @@
 use async_trait::async_trait;
-use serde_json::Value;
+use reqwest::Url;
+use serde::Deserialize;
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricsContent, LyricsFetcher, LyricsItem, score::Scorer};
+use crate::{config::SourceSettings, error::LyricsError, song::SongInfo};
 
-// Spotify音乐实现
-#[allow(dead_code)]
-#[derive(Default)]
+#[derive(Debug, Deserialize)]
+struct SuggestResponse {
+    data: Vec<Song>,
+}
+
+// data
+#[derive(Debug, Deserialize)]
+struct Song {
+    title: String,
+    artist: Artist,
+    #[serde(default)]
+    album: Option<Album>,
+    /// 时长，单位：秒
+    #[serde(default)]
+    duration: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Artist {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Album {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LyricsData {
+    #[serde(default)]
+    lyrics: String,
+}
+
+// lyrics.ovh 实现，只提供无时间的歌词
 pub(super) struct OvhFetcher {
     base: BaseFetcher,
 }
 
-#[async_trait]
-impl LyricsFetcher for OvhFetcher {
-    async fn search_lyric(&self, _song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
-        Err(LyricsError::NoLyricsFound)
-    }
-    async fn download_lyric(&self, _item: &LyricsItem) -> Result<String, LyricsError> {
-        Err(LyricsError::NoLyricsFound)
+impl OvhFetcher {
+    pub(super) fn new(settings: &SourceSettings, scorer: Scorer) -> Self {
+        Self {
+            base: BaseFetcher::new(settings, scorer),
+        }
     }
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
-        // 假设使用的第三方Spotify歌词API如下（实际应使用真实的API）
-        let ovh_api = "https://api.lyrics.ovh/v1";
 
-        let api_url = format!("{}/{}/{}", ovh_api, song.artist, song.title);
+    // 路径参数需要编码
+    fn api_url(&self, url: &str, segments: &[&str]) -> Result<Url, LyricsError> {
+        let mut url = Url::parse(&self.base.url(url)).map_err(anyhow::Error::from)?;
+        url.path_segments_mut()
+            .map_err(|_| LyricsError::NoLyricsFound)?
+            .extend(segments);
+        Ok(url)
+    }
 
-        // let encoded_artist = urlencoding::encode(&song.artist);
-        // let encoded_title = urlencoding::encode(&song.title);
+    async fn get_lyric(&self, artist: &str, title: &str) -> Result<LyricsContent, LyricsError> {
+        let lyric_url = self.api_url("https://api.lyrics.ovh/v1", &[artist, title])?;
         let request = self
             .base
             .client
-            .get(api_url)
-            // .query(&[("track", &song.title), ("artist", &song.artist)])
-            .header("Accept", "application/json")
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-            );
-
-        let json: Value = self.base.fetch_with_retry(request).await?;
-        let lyrics = json["lyrics"].as_str().ok_or(LyricsError::NoLyricsFound)?;
-
-        if lyrics.is_empty() {
+            .get(lyric_url)
+            .header("Accept", "application/json");
+
+        let data: LyricsData = self.base.fetch_with_retry(request).await?;
+        let lyric = clean_lyric(&data.lyrics);
+        if lyric.is_empty() {
             return Err(LyricsError::NoLyricsFound);
         }
+        Ok(LyricsContent::new(lyric))
+    }
+}
+
+#[async_trait]
+impl LyricsFetcher for OvhFetcher {
+    async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
+        let term = format!("{} {}", song.artist, song.title);
+        let search_url = self.api_url("https://api.lyrics.ovh/suggest", &[term.trim()])?;
+        let request = self
+            .base
+            .client
+            .get(search_url)
+            .header("Accept", "application/json");
+
+        let data: SuggestResponse = self.base.fetch_with_retry(request).await?;
+
+        let list: Vec<LyricsItem> = data
+            .data
+            .into_iter()
+            .map(|s| LyricsItem {
+                source: self.source_name().into(),
+                params: vec![
+                    ("artist".to_string(), s.artist.name.clone()),
+                    ("title".to_string(), s.title.clone()),
+                ],
+                title: s.title,
+                artist: s.artist.name,
+                album: s.album.map(|a| a.title).unwrap_or_default(),
+                duration: s.duration.map(|d| d as f64),
+            })
+            .collect();
+
+        log::debug!("Get List: {:?}", list);
+
+        if !list.is_empty() {
+            Ok(list)
+        } else {
+            Err(LyricsError::NoLyricsFound)
+        }
+    }
 
-        // 假设第三方API返回的歌词不需要解码或特殊处理
-        Ok(lyrics.to_string())
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let param = |key: &str| {
+            item.params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .ok_or(LyricsError::NoLyricsFound)
+        };
+        self.get_lyric(param("artist")?, param("title")?).await
+    }
+
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
+        // 先按播放器的作者与标题直接获取，失败时再搜索
+        if !song.artist.is_empty() {
+            match self.get_lyric(&song.artist, &song.title).await {
+                Ok(content) => {
+                    // 接口只返回歌词，条目只记录查询用的作者与标题，
+                    // 专辑与时长未知，评分低于能确认这两项的来源
+                    let item = LyricsItem {
+                        source: self.source_name().to_string(),
+                        title: song.title.clone(),
+                        artist: song.artist.clone(),
+                        album: String::new(),
+                        duration: None,
+                        params: vec![
+                            ("artist".to_string(), song.artist.clone()),
+                            ("title".to_string(), song.title.clone()),
+                        ],
+                    };
+                    return Ok((item, content));
+                }
+                Err(e) => log::debug!("lyrics.ovh direct lookup failed: {e}"),
+            }
+        }
+
+        let list = self.search_lyric(song).await?;
+        let item = self.base.scorer.best_match(list, song)?;
+        log::debug!("Get song: {:?} info: {:?}", item, song);
+        let content = self.download_lyric(&item).await?;
+        Ok((item, content))
     }
 
     fn source_name(&self) -> &'static str {
-        "Spotify"
+        "LyricsOvh"
     }
 }
+
+/// 统一换行，去掉开头的 "Paroles de la chanson ... par ..." 说明行
+fn clean_lyric(lyric: &str) -> String {
+    let lyric = lyric.replace("\r\n", "\n");
+    let lyric = match lyric.split_once('\n') {
+        Some((first, rest)) if first.starts_with("Paroles de la chanson") => rest,
+        _ => lyric.as_str(),
+    };
+    lyric.trim().to_string()
+}
@@ -1,9 +1,23 @@
+use std::io::Read;
+
 use async_trait::async_trait;
 use base64::{Engine, prelude::BASE64_STANDARD};
+use des::qrc_decrypt;
+use flate2::read::ZlibDecoder;
 use serde::Deserialize;
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{client::get_first, error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricsContent, LyricsFetcher, LyricsItem, score::Scorer};
+use crate::{
+    config::SourceSettings,
+    error::LyricsError,
+    song::{QrcParser, SongInfo},
+    utils::decode_hex,
+};
+
+mod des;
+
+/// 网页版站点，作为请求的 Origin 与 Referer
+const ORIGIN: &str = "https://y.qq.com";
 
 #[derive(Debug, Deserialize)]
 struct Response {
@@ -22,10 +36,13 @@ struct SongPack {
 }
 #[derive(Debug, Deserialize)]
 struct Song {
+    songid: u64,
     songmid: String,
     songname: String,
     singer: Vec<Artist>,
     albumname: String,
+    /// 时长，单位：秒
+    interval: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,14 +54,164 @@ struct Artist {
 #[derive(Debug, Deserialize)]
 struct LyricsData {
     lyric: String,
+    /// 翻译
+    #[serde(default)]
+    trans: String,
 }
 
 // QQ音乐实现
-#[derive(Default)]
 pub(super) struct QQMusicFetcher {
     base: BaseFetcher,
 }
 
+impl QQMusicFetcher {
+    pub(super) fn new(settings: &SourceSettings, scorer: Scorer) -> Self {
+        Self {
+            base: BaseFetcher::new(settings, scorer),
+        }
+    }
+
+    // Base64 解码歌词
+    fn decode_lyric(&self, encoded: &str) -> Result<String, LyricsError> {
+        let decoded = BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|_| LyricsError::LyricsDecodeError)?;
+
+        String::from_utf8(decoded).map_err(|_| LyricsError::LyricsDecodeError)
+    }
+
+    /// 请求来源站点，配置了 base_url 时与镜像地址一致
+    fn origin(&self) -> String {
+        self.base.url(ORIGIN)
+    }
+
+    fn referer(&self) -> String {
+        self.base.url(&format!("{ORIGIN}/n/ryqq/player"))
+    }
+
+    // QRC 解密：十六进制解码，3DES 解密，再 zlib 解压
+    fn decode_qrc(&self, encrypted: &str) -> Result<String, LyricsError> {
+        let bytes = decode_hex(encrypted).ok_or(LyricsError::LyricsDecodeError)?;
+        let decrypted = qrc_decrypt(&bytes);
+
+        let mut decoded = String::new();
+        ZlibDecoder::new(decrypted.as_slice())
+            .read_to_string(&mut decoded)
+            .map_err(|_| LyricsError::LyricsDecodeError)?;
+
+        Ok(decoded.trim_start_matches('\u{feff}').to_string())
+    }
+
+    // QRC 逐字歌词，转为 enhanced LRC 保存
+    async fn download_qrc(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let song_id = item
+            .params
+            .iter()
+            .find(|(k, _)| k == "songid")
+            .map(|(_, v)| v.as_str())
+            .ok_or(LyricsError::NoLyricsFound)?;
+
+        let qrc_url = "https://c.y.qq.com/qqmusic/fcgi-bin/lyric_download.fcg";
+        let request = self
+            .base
+            .client
+            .get(self.base.url(qrc_url))
+            .query(&[
+                ("version", "15"),
+                ("miniversion", "82"),
+                ("lrctype", "4"),
+                ("musicid", song_id),
+            ])
+            .header("Referer", self.referer());
+
+        let xml = self.base.fetch_text_with_retry(request).await?;
+        let xml = xml.replace("<!--", "").replace("-->", "");
+
+        let content = xml_value(&xml, "content").ok_or(LyricsError::NoLyricsFound)?;
+        let doc = QrcParser::parse(&self.decode_qrc(content)?).await?;
+
+        // 翻译为普通 LRC，音译为 QRC
+        let translation = xml_value(&xml, "contentts")
+            .and_then(|s| self.decode_qrc(s).ok())
+            .filter(|s| !s.trim().is_empty());
+        let romanization = match xml_value(&xml, "contentroma") {
+            Some(s) => match self.decode_qrc(s) {
+                Ok(roma) => QrcParser::parse(&roma).await.ok().map(|d| d.to_lrc()),
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        Ok(LyricsContent {
+            lyric: doc.to_lrc(),
+            translation,
+            romanization,
+        })
+    }
+
+    // 普通 LRC 歌词
+    async fn download_lrc(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        let mut params: Vec<(String, String)> = item
+            .params
+            .iter()
+            .filter(|(k, _)| k == "songmid")
+            .cloned()
+            .collect();
+        params.append(&mut vec![
+            ("format".to_string(), "json".to_string()),
+            ("g_tk".to_string(), "5381".to_string()),
+        ]);
+
+        // 2. 获取歌词
+        let lyrics_url = "https://c.y.qq.com/lyric/fcgi-bin/fcg_query_lyric_new.fcg";
+        let request = self
+            .base
+            .client
+            .get(self.base.url(lyrics_url))
+            .query(&params)
+            .header("Referer", self.referer())
+            .header("Origin", self.origin());
+
+        let data: LyricsData = self.base.fetch_with_retry(request).await?;
+
+        // 处理Base64解码
+        let re = self.decode_lyric(&data.lyric)?;
+        if re.is_empty() {
+            return Err(LyricsError::NoLyricsFound);
+        }
+
+        // 翻译解码失败时忽略
+        let translation = self
+            .decode_lyric(&data.trans)
+            .ok()
+            .filter(|s| !s.trim().is_empty());
+
+        Ok(LyricsContent {
+            lyric: re,
+            translation,
+            ..Default::default()
+        })
+    }
+}
+
+/// 读取 XML 标签内容，去掉 CDATA 包装
+fn xml_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    let value = xml[start..end].trim();
+    let value = value
+        .strip_prefix("<![CDATA[")
+        .and_then(|v| v.strip_suffix("]]>"))
+        .unwrap_or(value)
+        .trim();
+
+    match value.is_empty() {
+        true => None,
+        false => Some(value),
+    }
+}
+
 #[async_trait]
 impl LyricsFetcher for QQMusicFetcher {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
@@ -53,19 +220,18 @@ impl LyricsFetcher for QQMusicFetcher {
         let request= self
             .base
             .client
-            .get(search_url)
+            .get(self.base.url(search_url))
             .query(&[
                 ("w",  format!("{} {}", song.title, song.artist).as_str()),
                 ("format", "json"),
                 ("p","1"), // page
-                ("n", "1"),// 每页数量
+                ("n", "10"),// 每页数量
                 ("cr", "1"), // 中文
                 ("t","0") // 搜索类型 0 歌曲
                 // ("g_tk", "5381"), //
             ])
-            .header("Referer", "https://y.qq.com/n/ryqq/player")
-            .header("Host", "c.y.qq.com")
-            .header("Origin", "https://y.qq.com")
+            .header("Referer", self.referer())
+            .header("Origin", self.origin())
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/102.0.5005.63 Safari/537.36");
 
         let data = self.base.fetch_with_retry::<Response>(request).await?;
@@ -84,15 +250,20 @@ impl LyricsFetcher for QQMusicFetcher {
                     .iter()
                     .map(|aa| aa.name.clone())
                     .collect::<Vec<String>>()
-                    .join(" ");
+                    .join("/");
                 let album = s.albumname;
-                let params = vec![("songmid".to_string(), s.songmid)];
+                let duration = s.interval.map(|d| d as f64);
+                let params = vec![
+                    ("songmid".to_string(), s.songmid),
+                    ("songid".to_string(), s.songid.to_string()),
+                ];
 
                 LyricsItem {
                     source,
                     title,
                     artist,
                     album,
+                    duration,
                     params,
                 }
             })
@@ -107,38 +278,18 @@ impl LyricsFetcher for QQMusicFetcher {
         }
     }
 
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError> {
-        let mut params = item.params.clone();
-        params.append(&mut vec![
-            ("format".to_string(), "json".to_string()),
-            ("g_tk".to_string(), "5381".to_string()),
-        ]);
-
-        // 2. 获取歌词
-        let lyrics_url = "https://c.y.qq.com/lyric/fcgi-bin/fcg_query_lyric_new.fcg";
-        let request = self
-            .base
-            .client
-            .get(lyrics_url)
-            .query(&params)
-            .header("Referer", "https://y.qq.com/n/ryqq/player")
-            .header("Host", "c.y.qq.com")
-            .header("Origin", "https://y.qq.com");
-
-        let data: LyricsData = self.base.fetch_with_retry(request).await?;
-
-        // 处理Base64解码
-        let decoded = BASE64_STANDARD
-            .decode(data.lyric)
-            .map_err(|_| LyricsError::LyricsDecodeError)?;
-
-        let re = String::from_utf8(decoded).map_err(|_| LyricsError::LyricsDecodeError)?;
-        if re.is_empty() {
-            return Err(LyricsError::NoLyricsFound);
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsContent, LyricsError> {
+        match self.download_qrc(item).await {
+            Ok(content) => return Ok(content),
+            Err(e) => log::warn!("QRC unavailable, fallback to LRC: {e}"),
         }
-        Ok(re)
+        self.download_lrc(item).await
     }
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
+
+    async fn fetch_lyric(
+        &self,
+        song: &SongInfo,
+    ) -> Result<(LyricsItem, LyricsContent), LyricsError> {
         log::debug!("QQ search");
 
         // let song_mid = data
@@ -170,9 +321,10 @@ impl LyricsFetcher for QQMusicFetcher {
         // log::debug!("song mid : {song_mid}");
 
         let list = self.search_lyric(song).await?;
-        let item = get_first(list, song)?;
+        let item = self.base.scorer.best_match(list, song)?;
         log::debug!("Get song: {:?} info: {:?}", item, song);
-        self.download_lyric(&item).await
+        let content = self.download_lyric(&item).await?;
+        Ok((item, content))
     }
 
     fn source_name(&self) -> &'static str {
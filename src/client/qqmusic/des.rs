@@ -0,0 +1,247 @@
+//! QQ 音乐 QRC 使用的 3DES 解密
+//!
+//! QQ 的实现与标准 DES 有两处差异：按小端 32 位字读写数据块和密钥，
+//! 以及 S 盒中的两个数值，见 [`SBOX`]。
+
+/// QRC 3DES 密钥
+const QRC_KEY: &[u8; 24] = b"!@#)(*$%123ZXC!@!@#)(NHL";
+
+const IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, 62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8, 57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, 61,
+    53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+const FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, 38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29, 36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27,
+    34, 2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+const E: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, 8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, 16, 17, 18,
+    19, 20, 21, 20, 21, 22, 23, 24, 25, 24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+const P: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, 2, 8, 24, 14, 32, 27, 3, 9, 19,
+    13, 30, 6, 22, 11, 4, 25,
+];
+
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, 10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60,
+    52, 44, 36, 63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, 14, 6, 61, 53, 45, 37, 29,
+    21, 13, 5, 28, 20, 12, 4,
+];
+
+const PC2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, 23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, 41, 52,
+    31, 37, 47, 55, 30, 40, 51, 45, 33, 48, 44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+/// S 盒，S2 第 2 行第 8 列为 15 (标准为 14)，S4 第 4 行第 6 列为 10 (标准为 1)
+const SBOX: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, //
+        0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8, //
+        4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, //
+        15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, //
+        3, 13, 4, 7, 15, 2, 8, 15, 12, 0, 1, 10, 6, 9, 11, 5, //
+        0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, //
+        13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, //
+        13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1, //
+        13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, //
+        1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, //
+        13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9, //
+        10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, //
+        3, 15, 0, 6, 10, 10, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, //
+        14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6, //
+        4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, //
+        11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, //
+        10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8, //
+        9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, //
+        4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, //
+        13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6, //
+        1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, //
+        6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, //
+        1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2, //
+        7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, //
+        2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Encrypt,
+    Decrypt,
+}
+
+/// 按表置换，表中位置从最高位开始计数，从 1 开始
+fn permute(input: u64, width: u32, table: &[u8]) -> u64 {
+    table.iter().fold(0, |out, &pos| {
+        (out << 1) | ((input >> (width - pos as u32)) & 1)
+    })
+}
+
+/// QQ 按小端 32 位字读取 8 字节
+fn read_block(bytes: &[u8]) -> u64 {
+    let hi = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let lo = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    ((hi as u64) << 32) | lo as u64
+}
+
+fn write_block(block: u64) -> [u8; 8] {
+    let mut out = [0; 8];
+    out[..4].copy_from_slice(&((block >> 32) as u32).to_le_bytes());
+    out[4..].copy_from_slice(&(block as u32).to_le_bytes());
+    out
+}
+
+/// 生成 16 轮子密钥
+fn key_schedule(key: &[u8], mode: Mode) -> [u64; 16] {
+    let key = permute(read_block(key), 64, &PC1);
+    let mut c = (key >> 28) as u32;
+    let mut d = (key & 0x0fff_ffff) as u32;
+
+    let mut schedule = [0u64; 16];
+    for (i, shift) in SHIFTS.iter().enumerate() {
+        c = ((c << shift) | (c >> (28 - shift))) & 0x0fff_ffff;
+        d = ((d << shift) | (d >> (28 - shift))) & 0x0fff_ffff;
+        let index = match mode {
+            Mode::Encrypt => i,
+            Mode::Decrypt => 15 - i,
+        };
+        schedule[index] = permute(((c as u64) << 28) | d as u64, 56, &PC2);
+    }
+    schedule
+}
+
+fn feistel(half: u32, subkey: u64) -> u32 {
+    let expanded = permute(half as u64, 32, &E) ^ subkey;
+
+    let mut out = 0u32;
+    for (i, sbox) in SBOX.iter().enumerate() {
+        let chunk = ((expanded >> (42 - 6 * i)) & 0x3f) as usize;
+        let row = ((chunk & 0x20) >> 4) | (chunk & 0x01);
+        let col = (chunk >> 1) & 0x0f;
+        out = (out << 4) | sbox[row * 16 + col] as u32;
+    }
+
+    permute(out as u64, 32, &P) as u32
+}
+
+fn crypt_block(block: u64, schedule: &[u64; 16]) -> u64 {
+    let block = permute(block, 64, &IP);
+    let mut left = (block >> 32) as u32;
+    let mut right = block as u32;
+
+    for subkey in schedule {
+        let next = left ^ feistel(right, *subkey);
+        left = right;
+        right = next;
+    }
+
+    permute(((right as u64) << 32) | left as u64, 64, &FP)
+}
+
+/// 3DES (EDE) 解密，ECB 模式，不足 8 字节的尾部丢弃
+pub(super) fn qrc_decrypt(data: &[u8]) -> Vec<u8> {
+    let schedules = [
+        key_schedule(&QRC_KEY[16..], Mode::Decrypt),
+        key_schedule(&QRC_KEY[8..16], Mode::Encrypt),
+        key_schedule(&QRC_KEY[..8], Mode::Decrypt),
+    ];
+
+    data.chunks_exact(8)
+        .flat_map(|chunk| {
+            let block = schedules.iter().fold(read_block(chunk), |block, schedule| {
+                crypt_block(block, schedule)
+            });
+            write_block(block)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+    use crate::utils::decode_hex;
+
+    /// 由独立的参考实现加密，参考实现在使用标准 S 盒时与标准 DES 结果一致
+    const CIPHER: &str = "08564852004bd27dfe52b816313792d89adbede49be70e16b5cec626ae991f8d";
+    const PLAIN: &[u8] = b"[ti:Qing Tian]\n[ar:Jay Chou]\n\0\0\0";
+
+    #[test]
+    fn decrypts_known_answer() {
+        let data = decode_hex(CIPHER).unwrap();
+
+        assert_eq!(qrc_decrypt(&data), PLAIN);
+    }
+
+    #[test]
+    fn drops_partial_block() {
+        let mut data = decode_hex(CIPHER).unwrap();
+        data.extend_from_slice(&[0xff; 5]);
+
+        assert_eq!(qrc_decrypt(&data), PLAIN);
+        assert!(qrc_decrypt(&data[..7]).is_empty());
+    }
+
+    #[test]
+    fn round_trips_block() {
+        let schedules = [
+            key_schedule(&QRC_KEY[..8], Mode::Encrypt),
+            key_schedule(&QRC_KEY[..8], Mode::Decrypt),
+        ];
+        let block = read_block(&PLAIN[..8]);
+
+        let encrypted = crypt_block(block, &schedules[0]);
+        assert_ne!(encrypted, block);
+        assert_eq!(crypt_block(encrypted, &schedules[1]), block);
+    }
+
+    #[test]
+    fn decrypts_fixture_payload() {
+        let xml = include_str!("../../../tests/fixtures/qqmusic/qrc.xml");
+        let start = xml.find("<content><![CDATA[").unwrap() + "<content><![CDATA[".len();
+        let end = start + xml[start..].find(']').unwrap();
+        let data = decode_hex(&xml[start..end]).unwrap();
+
+        let mut decoded = String::new();
+        ZlibDecoder::new(qrc_decrypt(&data).as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(
+            decoded,
+            include_str!("../../../tests/fixtures/qqmusic/qrc_plain.xml")
+        );
+    }
+}
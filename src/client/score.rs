@@ -0,0 +1,498 @@
+use std::{collections::HashSet, fmt};
+
+use strsim::normalized_levenshtein;
+
+use super::{LyricsContent, LyricsItem};
+use crate::{
+    config::Sources,
+    error::LyricsError,
+    song::{LyricParser, SongInfo},
+};
+
+/// 候选歌词评分，各项取值 0~1
+#[derive(Debug, Clone, Default)]
+pub struct Score {
+    /// 标题相似度
+    pub title: f64,
+    /// 作者相似度
+    pub artist: f64,
+    /// 专辑相似度
+    pub album: f64,
+    /// 时长接近程度
+    pub duration: f64,
+    /// 是否带时间
+    pub synced: f64,
+    /// 是否逐字
+    pub word_timed: f64,
+    /// 来源优先级，按配置顺序递减
+    pub priority: f64,
+}
+
+impl Score {
+    const TITLE_WEIGHT: f64 = 0.4;
+    const ARTIST_WEIGHT: f64 = 0.25;
+    const ALBUM_WEIGHT: f64 = 0.1;
+    const DURATION_WEIGHT: f64 = 0.1;
+    const SYNCED_WEIGHT: f64 = 0.15;
+    /// 逐字歌词额外加分
+    const WORD_TIMED_BONUS: f64 = 0.05;
+    /// 来源优先级加分，仅在评分相近时起作用
+    const PRIORITY_BONUS: f64 = 0.03;
+
+    /// 匹配度低于该值视为不同歌曲
+    const MATCH_THRESHOLD: f64 = 0.65;
+    /// 歌词时长允许比歌曲长出的范围，单位：秒
+    const DURATION_GRACE: f64 = 5.0;
+
+    /// 按下载的歌词内容补充评分，内容为错误页等非歌词时返回错误
+    pub async fn apply_content(
+        &mut self,
+        song: &SongInfo,
+        content: &LyricsContent,
+    ) -> Result<(), LyricsError> {
+        let doc = match LyricParser::parse(content.lyric.clone(), song.duration).await {
+            Ok(doc) => doc,
+            Err(LyricsError::LyricsValidationFailed) => {
+                return Err(LyricsError::LyricsValidationFailed);
+            }
+            Err(_) => return Ok(()),
+        };
+
+        self.synced = doc.synced as u8 as f64;
+        self.word_timed = doc.lines.iter().any(|line| line.words.is_some()) as u8 as f64;
+
+        // 歌词比歌曲长出太多时视为不同版本
+        if song.duration > 0.0 && doc.synced {
+            let length = doc
+                .metadata
+                .length
+                .or_else(|| doc.lines.last().map(|line| line.timestamp_start));
+            if length.is_some_and(|length| length > song.duration + Self::DURATION_GRACE) {
+                self.duration = 0.0;
+            }
+        }
+        Ok(())
+    }
+
+    /// 标题、作者、专辑的匹配度
+    pub fn matched(&self) -> f64 {
+        (self.title * Self::TITLE_WEIGHT
+            + self.artist * Self::ARTIST_WEIGHT
+            + self.album * Self::ALBUM_WEIGHT)
+            / (Self::TITLE_WEIGHT + Self::ARTIST_WEIGHT + Self::ALBUM_WEIGHT)
+    }
+
+    /// 是否达到匹配阈值且时长在误差范围内
+    pub fn is_match(&self) -> bool {
+        self.matched() >= Self::MATCH_THRESHOLD && self.duration > 0.0
+    }
+
+    /// 总分
+    pub fn total(&self) -> f64 {
+        self.title * Self::TITLE_WEIGHT
+            + self.artist * Self::ARTIST_WEIGHT
+            + self.album * Self::ALBUM_WEIGHT
+            + self.duration * Self::DURATION_WEIGHT
+            + self.synced * Self::SYNCED_WEIGHT
+            + self.word_timed * Self::WORD_TIMED_BONUS
+            + self.priority * Self::PRIORITY_BONUS
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "total={:.3} title={:.2} artist={:.2} album={:.2} duration={:.2} synced={:.0} word={:.0} priority={:.2}",
+            self.total(),
+            self.title,
+            self.artist,
+            self.album,
+            self.duration,
+            self.synced,
+            self.word_timed,
+            self.priority
+        )
+    }
+}
+
+/// 评分参数，由来源配置生成
+#[derive(Debug, Clone, Copy)]
+pub struct Scorer {
+    /// 搜索结果与歌曲时长允许的误差，单位：秒，0 表示不检查
+    pub duration_tolerance: f64,
+}
+
+impl Default for Scorer {
+    fn default() -> Self {
+        Self::new(&Sources::default())
+    }
+}
+
+impl Scorer {
+    /// 时长未知时的得分，不排除候选，但低于误差范围内时长已知的结果
+    const UNKNOWN_DURATION: f64 = 0.4;
+
+    pub fn new(config: &Sources) -> Self {
+        Self {
+            duration_tolerance: config.duration_tolerance,
+        }
+    }
+
+    /// 按搜索结果的标题、作者、专辑评分
+    pub fn score(&self, song: &SongInfo, item: &LyricsItem) -> Score {
+        Score {
+            title: title_similarity(&song.title, &item.title),
+            artist: artist_similarity(&song.artist, &item.artist),
+            album: title_similarity(&song.album, &item.album),
+            duration: self.duration_similarity(song.duration, item.duration),
+            ..Default::default()
+        }
+    }
+
+    /// 从搜索结果中选出匹配度最高的一项，匹配度相同时时长更接近的优先，均低于阈值时视为未找到
+    pub fn best_match(
+        &self,
+        list: Vec<LyricsItem>,
+        song: &SongInfo,
+    ) -> Result<LyricsItem, LyricsError> {
+        let mut best: Option<((f64, f64), LyricsItem)> = None;
+
+        for item in list {
+            let score = self.score(song, &item);
+            log::debug!(
+                "Match {} - {} [{}]: {:.3}",
+                item.artist,
+                item.title,
+                item.album,
+                score.matched()
+            );
+            if !score.is_match() {
+                continue;
+            }
+            let rank = (score.matched(), score.duration);
+            if best.as_ref().is_none_or(|(best, _)| rank > *best) {
+                best = Some((rank, item));
+            }
+        }
+
+        best.map(|(_, item)| item).ok_or(LyricsError::NoLyricsFound)
+    }
+
+    /// 时长接近程度，误差内线性递减至 0.5，超出为 0，无法比较时为 [`Self::UNKNOWN_DURATION`]
+    fn duration_similarity(&self, song: f64, item: Option<f64>) -> f64 {
+        let tolerance = self.duration_tolerance;
+        match item {
+            Some(item) if song > 0.0 && item > 0.0 && tolerance > 0.0 => {
+                let delta = (song - item).abs();
+                if delta > tolerance {
+                    0.0
+                } else {
+                    1.0 - delta / tolerance * 0.5
+                }
+            }
+            _ => Self::UNKNOWN_DURATION,
+        }
+    }
+}
+
+/// 标题相似度，去掉括号后缀后比较，仅后缀不同时略微扣分
+fn title_similarity(query: &str, candidate: &str) -> f64 {
+    let (query_full, candidate_full) = (fold(query), fold(candidate));
+    if query_full.is_empty() || query_full == candidate_full {
+        return 1.0;
+    }
+
+    let query = compact(&strip_suffix(&query_full));
+    let candidate = compact(&strip_suffix(&candidate_full));
+    if query.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+
+    let score = normalized_levenshtein(&query, &candidate);
+    if score == 1.0 && compact(&query_full) != compact(&candidate_full) {
+        0.9
+    } else {
+        score
+    }
+}
+
+/// 作者相似度，按作者集合的重合程度计算
+fn artist_similarity(query: &str, candidate: &str) -> f64 {
+    let query = split_artists(query);
+    if query.is_empty() {
+        return 1.0;
+    }
+    let candidate = split_artists(candidate);
+    if candidate.is_empty() {
+        return 0.0;
+    }
+
+    // 每位作者取最相近的一位，较少一方全部命中即视为匹配
+    let (small, large) = if query.len() <= candidate.len() {
+        (&query, &candidate)
+    } else {
+        (&candidate, &query)
+    };
+    let sum: f64 = small
+        .iter()
+        .map(|a| {
+            large
+                .iter()
+                .map(|b| normalized_levenshtein(a, b))
+                .fold(0.0, f64::max)
+        })
+        .sum();
+
+    sum / small.len() as f64
+}
+
+/// 拆分多位作者
+fn split_artists(s: &str) -> HashSet<String> {
+    let mut s = fold(s);
+    for sep in [" feat.", " feat ", " ft.", " featuring ", " with "] {
+        s = s.replace(sep, "/");
+    }
+
+    s.split(['/', '&', ',', '、', ';', '|'])
+        .map(compact)
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+/// 全角转半角、繁体转简体、转小写
+fn fold(s: &str) -> String {
+    let half: String = s
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            _ => c,
+        })
+        .collect();
+
+    fast2s::convert(&half).to_lowercase().trim().to_string()
+}
+
+/// 去掉括号内容及 feat. 之后的部分，如 "(Live)"、"[Remastered]"
+fn strip_suffix(s: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0usize;
+    for c in s.chars() {
+        match c {
+            '(' | '[' | '{' | '【' | '〔' | '「' | '『' => depth += 1,
+            ')' | ']' | '}' | '】' | '〕' | '」' | '』' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+
+    let end = [" feat.", " ft.", " - "]
+        .iter()
+        .filter_map(|sep| out.find(sep))
+        .min()
+        .unwrap_or(out.len());
+    out.truncate(end);
+    out
+}
+
+/// 仅保留字母数字
+fn compact(s: &str) -> String {
+    s.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, artist: &str, duration: Option<f64>) -> LyricsItem {
+        LyricsItem {
+            source: "Test".to_string(),
+            title: title.to_string(),
+            artist: artist.to_string(),
+            album: String::new(),
+            duration,
+            params: Vec::new(),
+        }
+    }
+
+    fn song(title: &str, artist: &str, duration: f64) -> SongInfo {
+        SongInfo {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            duration,
+            ..Default::default()
+        }
+    }
+
+    fn scorer(duration_tolerance: f64) -> Scorer {
+        Scorer { duration_tolerance }
+    }
+
+    #[test]
+    fn fold_table() {
+        let cases = [
+            ("Ｈｅｌｌｏ　Ｗｏｒｌｄ", "hello world"),
+            ("（Ｌｉｖｅ）", "(live)"),
+            ("周杰倫", "周杰伦"),
+            ("後來", "后来"),
+            ("  ABC ", "abc"),
+            ("晴天", "晴天"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(fold(input), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn split_artists_table() {
+        let cases: [(&str, &[&str]); 7] = [
+            ("周杰伦", &["周杰伦"]),
+            ("周杰伦/费玉清", &["周杰伦", "费玉清"]),
+            ("Jay Chou & Gary Yang", &["jaychou", "garyyang"]),
+            ("A feat. B", &["a", "b"]),
+            ("A ft. B, C", &["a", "b", "c"]),
+            ("A featuring B with C", &["a", "b", "c"]),
+            ("周杰倫、溫嵐", &["周杰伦", "温岚"]),
+        ];
+        for (input, expected) in cases {
+            let expected: HashSet<String> = expected.iter().map(|s| s.to_string()).collect();
+            assert_eq!(split_artists(input), expected, "{input:?}");
+        }
+        assert!(split_artists(" / & ").is_empty());
+    }
+
+    #[test]
+    fn strip_suffix_table() {
+        let cases = [
+            ("yellow (live)", "yellow "),
+            ("yellow [remastered]", "yellow "),
+            ("yellow - remastered 2011", "yellow"),
+            ("yellow feat. someone", "yellow"),
+            ("晴天【现场版】", "晴天"),
+            ("晴天 (live (2004))", "晴天 "),
+            ("a-ha", "a-ha"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(strip_suffix(input), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn title_similarity_table() {
+        let cases = [
+            ("晴天", "晴天", 1.0),
+            ("晴天", "晴天 (Live)", 0.9),
+            ("Yellow", "Yellow - Remastered 2011", 0.9),
+            ("Yellow", "ＹＥＬＬＯＷ", 1.0),
+            ("", "anything", 1.0),
+            ("晴天", "(Live)", 0.0),
+        ];
+        for (query, candidate, expected) in cases {
+            assert_eq!(
+                title_similarity(query, candidate),
+                expected,
+                "{query:?} {candidate:?}"
+            );
+        }
+        assert!(title_similarity("晴天", "七里香") < 0.5);
+    }
+
+    #[test]
+    fn artist_similarity_table() {
+        let cases = [
+            ("周杰伦", "周杰倫", 1.0),
+            ("周杰伦", "周杰伦/费玉清", 1.0),
+            ("Coldplay feat. Rihanna", "Rihanna & Coldplay", 1.0),
+            ("", "anyone", 1.0),
+            ("周杰伦", "", 0.0),
+        ];
+        for (query, candidate, expected) in cases {
+            assert_eq!(
+                artist_similarity(query, candidate),
+                expected,
+                "{query:?} {candidate:?}"
+            );
+        }
+        assert!(artist_similarity("周杰伦", "林俊杰") < 0.5);
+    }
+
+    #[test]
+    fn duration_similarity_table() {
+        let cases = [
+            (10.0, 200.0, Some(200.0), 1.0),
+            (10.0, 200.0, Some(205.0), 0.75),
+            (10.0, 200.0, Some(190.0), 0.5),
+            (10.0, 200.0, Some(189.0), 0.0),
+            (10.0, 200.0, None, Scorer::UNKNOWN_DURATION),
+            (10.0, 0.0, Some(200.0), Scorer::UNKNOWN_DURATION),
+            (0.0, 200.0, Some(100.0), Scorer::UNKNOWN_DURATION),
+            (2.0, 200.0, Some(203.0), 0.0),
+        ];
+        for (tolerance, song, item, expected) in cases {
+            assert_eq!(
+                scorer(tolerance).duration_similarity(song, item),
+                expected,
+                "{tolerance} {song} {item:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn match_threshold_boundary() {
+        let score = |matched: f64, duration: f64| Score {
+            title: matched,
+            artist: matched,
+            album: matched,
+            duration,
+            ..Default::default()
+        };
+
+        assert!(score(0.66, 1.0).is_match());
+        assert!(score(Score::MATCH_THRESHOLD, 1.0).is_match());
+        assert!(!score(0.64, 1.0).is_match());
+        assert!(!score(1.0, 0.0).is_match());
+    }
+
+    #[test]
+    fn best_match_uses_tolerance() {
+        let song = song("晴天", "周杰伦", 269.0);
+        let list = vec![
+            item("晴天 (Live)", "周杰伦", Some(330.0)),
+            item("晴天", "周杰倫", Some(275.0)),
+            item("七里香", "周杰伦", Some(269.0)),
+        ];
+
+        let best = scorer(10.0).best_match(list.clone(), &song).unwrap();
+        assert_eq!(best.title, "晴天");
+
+        // 误差更小时只剩不同的歌曲，视为未找到
+        let err = scorer(2.0).best_match(list.clone(), &song).unwrap_err();
+        assert!(matches!(err, LyricsError::NoLyricsFound), "{err}");
+
+        // 不检查时长时选标题最接近的一项
+        let best = scorer(0.0).best_match(list, &song).unwrap();
+        assert_eq!(best.title, "晴天");
+    }
+
+    #[test]
+    fn unknown_duration_is_neutral() {
+        let song = song("晴天", "周杰伦", 269.0);
+        let scorer = scorer(10.0);
+
+        // 时长未知不排除候选
+        let unknown = scorer.score(&song, &item("晴天", "周杰伦", None));
+        assert!(unknown.is_match());
+
+        // 但低于误差范围内的结果，即使在误差边缘
+        let edge = scorer.score(&song, &item("晴天", "周杰伦", Some(279.0)));
+        assert!(unknown.total() < edge.total());
+
+        let list = vec![
+            item("晴天", "周杰伦", None),
+            item("晴天", "周杰伦", Some(278.0)),
+        ];
+        let best = scorer.best_match(list, &song).unwrap();
+        assert_eq!(best.duration, Some(278.0));
+    }
+}
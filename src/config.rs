@@ -1,7 +1,12 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, MapAccess, Visitor, value::MapAccessDeserializer},
+};
 use std::{
-    fs,
+    collections::HashMap,
+    fmt, fs,
+    marker::PhantomData,
     path::PathBuf,
     sync::{OnceLock, RwLock},
 };
@@ -21,6 +26,7 @@ pub struct Config {
     pub player_filter: PlayerFilter,
     pub ui: Ui,
     pub sources: Sources,
+    pub cache: Cache,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,6 +64,12 @@ pub struct Ui {
     pub time: bool,
     #[serde(default = "default_true")]
     pub progress_bar: bool,
+    /// 显示翻译
+    #[serde(default = "default_true")]
+    pub translation: bool,
+    /// 显示音译 (罗马音/拼音)
+    #[serde(default)]
+    pub romanization: bool,
 }
 
 impl Default for Ui {
@@ -66,28 +78,320 @@ impl Default for Ui {
             title: true,
             time: false,
             progress_bar: true,
+            translation: true,
+            romanization: false,
         }
     }
 }
 
+/// 缓存限制，退出时与 `cache gc` 命令按此清理
+#[derive(Debug, Deserialize)]
+pub struct Cache {
+    /// 缓存目录的最大大小，单位：MB，超出时删除最久未访问的歌词，0 表示不限制
+    #[serde(default = "default_cache_max_size")]
+    pub max_size: u64,
+    /// 超过该天数未访问的歌词被删除，0 表示不限制
+    #[serde(default = "default_cache_max_age")]
+    pub max_age: u64,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            max_size: default_cache_max_size(),
+            max_age: default_cache_max_age(),
+        }
+    }
+}
+
+fn default_cache_max_size() -> u64 {
+    50
+}
+
+fn default_cache_max_age() -> u64 {
+    365
+}
+
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Sources {
-    #[serde(default = "default_true")]
-    pub netease: bool,
-    #[serde(default = "default_true")]
-    pub qq: bool,
-    #[serde(default = "default_true")]
-    pub kugou: bool,
+    /// 来源优先级，靠前的来源在评分相近时优先
+    #[serde(default = "default_source_order")]
+    pub order: Vec<String>,
+    /// 本地歌词，优先于网络来源
+    #[serde(default, deserialize_with = "source::<_, LocalTable>")]
+    pub local: SourceSettings,
+    /// 音频文件标签中内嵌的歌词，优先于网络来源
+    #[serde(default, deserialize_with = "source::<_, EmbeddedTable>")]
+    pub embedded: SourceSettings,
+    #[serde(default, deserialize_with = "source::<_, NetworkTable>")]
+    pub netease: SourceSettings,
+    #[serde(default, deserialize_with = "source::<_, NetworkTable>")]
+    pub qq: SourceSettings,
+    #[serde(default, deserialize_with = "source::<_, NetworkTable>")]
+    pub kugou: SourceSettings,
+    #[serde(default, deserialize_with = "source::<_, NetworkTable>")]
+    pub lrclib: SourceSettings,
+    /// lyrics.ovh，只有无时间的歌词
+    #[serde(default, deserialize_with = "source::<_, NetworkTable>")]
+    pub ovh: SourceSettings,
+    /// 外部命令，需要配置 command 并加入 order
+    #[serde(default, deserialize_with = "source::<_, CommandTable>")]
+    pub command: SourceSettings,
+    /// 所有来源并发获取的截止时间，单位：秒
+    #[serde(default = "default_deadline")]
+    pub deadline: u64,
+    /// 搜索结果与歌曲时长允许的误差，单位：秒，超出视为不同版本，0 表示不检查
+    #[serde(default = "default_duration_tolerance")]
+    pub duration_tolerance: f64,
+    /// 所有来源都没有歌词时的缓存时间，单位：秒，0 表示不缓存
+    #[serde(default = "default_not_found_ttl")]
+    pub not_found_ttl: u64,
 }
 
 impl Default for Sources {
     fn default() -> Self {
         Sources {
-            netease: true,
-            qq: true,
-            kugou: true,
+            order: default_source_order(),
+            local: SourceSettings::default(),
+            embedded: SourceSettings::default(),
+            netease: SourceSettings::default(),
+            qq: SourceSettings::default(),
+            kugou: SourceSettings::default(),
+            lrclib: SourceSettings::default(),
+            ovh: SourceSettings::default(),
+            command: SourceSettings::default(),
+            deadline: default_deadline(),
+            duration_tolerance: default_duration_tolerance(),
+            not_found_ttl: default_not_found_ttl(),
+        }
+    }
+}
+
+impl Sources {
+    /// 按名称获取来源配置
+    pub fn get(&self, name: &str) -> Option<&SourceSettings> {
+        match name {
+            "local" => Some(&self.local),
+            "embedded" => Some(&self.embedded),
+            "netease" => Some(&self.netease),
+            "qq" => Some(&self.qq),
+            "kugou" => Some(&self.kugou),
+            "lrclib" => Some(&self.lrclib),
+            "ovh" => Some(&self.ovh),
+            "command" => Some(&self.command),
+            _ => None,
+        }
+    }
+}
+
+fn default_source_order() -> Vec<String> {
+    vec![
+        "local".to_string(),
+        "embedded".to_string(),
+        "netease".to_string(),
+        "qq".to_string(),
+        "kugou".to_string(),
+        "lrclib".to_string(),
+        "ovh".to_string(),
+    ]
+}
+
+/// 单个来源的配置
+///
+/// 配置文件中每个来源可以写成 `netease = true` 的旧版写法，或该来源专属的配置表，
+/// 表中出现其他来源的配置项 (如 `[sources.netease]` 中的 `command`) 视为错误
+#[derive(Debug, Clone)]
+pub struct SourceSettings {
+    pub enabled: bool,
+    /// 请求超时，单位：秒
+    pub timeout: u64,
+    /// 失败重试次数
+    pub retries: u8,
+    /// 替换默认地址的协议与域名，如镜像地址
+    pub base_url: Option<String>,
+    pub user_agent: Option<String>,
+    /// 附加请求头
+    pub headers: HashMap<String, String>,
+    /// 外部命令来源的可执行文件
+    pub command: Option<String>,
+    /// 外部命令的参数
+    pub args: Vec<String>,
+    /// 本地来源的歌词目录
+    pub dirs: Vec<String>,
+    /// 本地来源在歌词目录中匹配的文件名，如 `{artist} - {title}`
+    pub patterns: Vec<String>,
+    /// 内嵌来源将选中的网络歌词写回音频文件标签
+    pub write: bool,
+}
+
+impl Default for SourceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout: default_source_timeout(),
+            retries: default_source_retries(),
+            base_url: None,
+            user_agent: None,
+            headers: HashMap::new(),
+            command: None,
+            args: Vec::new(),
+            dirs: Vec::new(),
+            patterns: Vec::new(),
+            write: false,
+        }
+    }
+}
+
+/// 读取布尔值或配置表 `T`，转换为 [`SourceSettings`]
+fn source<'de, D, T>(deserializer: D) -> Result<SourceSettings, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Into<SourceSettings>,
+{
+    struct SourceVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> Visitor<'de> for SourceVisitor<T>
+    where
+        T: Deserialize<'de> + Into<SourceSettings>,
+    {
+        type Value = SourceSettings;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a boolean or a source table")
+        }
+
+        fn visit_bool<E: de::Error>(self, enabled: bool) -> Result<Self::Value, E> {
+            Ok(SourceSettings {
+                enabled,
+                ..Default::default()
+            })
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            T::deserialize(MapAccessDeserializer::new(map)).map(Into::into)
         }
     }
+
+    deserializer.deserialize_any(SourceVisitor::<T>(PhantomData))
+}
+
+/// 网络来源 (netease、qq、kugou、lrclib、ovh) 的配置表
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetworkTable {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default = "default_source_timeout")]
+    timeout: u64,
+    #[serde(default = "default_source_retries")]
+    retries: u8,
+    base_url: Option<String>,
+    user_agent: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+}
+
+impl From<NetworkTable> for SourceSettings {
+    fn from(table: NetworkTable) -> Self {
+        Self {
+            enabled: table.enabled,
+            timeout: table.timeout,
+            retries: table.retries,
+            base_url: table.base_url,
+            user_agent: table.user_agent,
+            headers: table.headers,
+            ..Default::default()
+        }
+    }
+}
+
+/// 本地来源的配置表
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LocalTable {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    dirs: Vec<String>,
+    #[serde(default)]
+    patterns: Vec<String>,
+}
+
+impl From<LocalTable> for SourceSettings {
+    fn from(table: LocalTable) -> Self {
+        Self {
+            enabled: table.enabled,
+            dirs: table.dirs,
+            patterns: table.patterns,
+            ..Default::default()
+        }
+    }
+}
+
+/// 内嵌来源的配置表
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmbeddedTable {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    write: bool,
+}
+
+impl From<EmbeddedTable> for SourceSettings {
+    fn from(table: EmbeddedTable) -> Self {
+        Self {
+            enabled: table.enabled,
+            write: table.write,
+            ..Default::default()
+        }
+    }
+}
+
+/// 外部命令来源的配置表
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommandTable {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default = "default_source_timeout")]
+    timeout: u64,
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+impl From<CommandTable> for SourceSettings {
+    fn from(table: CommandTable) -> Self {
+        Self {
+            enabled: table.enabled,
+            timeout: table.timeout,
+            command: table.command,
+            args: table.args,
+            ..Default::default()
+        }
+    }
+}
+
+fn default_source_timeout() -> u64 {
+    5
+}
+
+fn default_source_retries() -> u8 {
+    3
+}
+
+fn default_deadline() -> u64 {
+    8
+}
+
+fn default_duration_tolerance() -> f64 {
+    10.0
+}
+
+fn default_not_found_ttl() -> u64 {
+    24 * 60 * 60
 }
 
 fn default_true() -> bool {
@@ -142,10 +446,95 @@ pub fn log_path() -> PathBuf {
     log_file
 }
 
-pub fn cache_path() -> PathBuf {
-    let cache_dir = dirs::home_dir()
+/// 旧版缓存直接放在配置目录中
+pub fn legacy_cache_path() -> PathBuf {
+    dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
-        .join(CONFIG_PATH);
-    ensure_parent_dir(&cache_dir);
+        .join(CONFIG_PATH)
+}
+
+pub fn cache_path() -> PathBuf {
+    let cache_dir = legacy_cache_path().join("cache");
+    if !cache_dir.exists() {
+        fs::create_dir_all(&cache_dir).ok();
+    }
     cache_dir
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Result<Sources, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    #[test]
+    fn boolean_sources_are_accepted() {
+        let sources = parse("netease = false\nlocal = true").unwrap();
+
+        assert!(!sources.netease.enabled);
+        assert!(sources.local.enabled);
+        assert_eq!(sources.netease.timeout, default_source_timeout());
+    }
+
+    #[test]
+    fn source_tables_fill_defaults() {
+        let sources = parse(
+            r#"
+            [netease]
+            base_url = "http://127.0.0.1:8080"
+            retries = 1
+
+            [kugou.headers]
+            Cookie = "a=b"
+
+            [local]
+            dirs = ["~/Music/Lyrics"]
+
+            [embedded]
+            write = true
+
+            [command]
+            command = "/bin/provider"
+            args = ["--lrc"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(sources.netease.enabled);
+        assert_eq!(sources.netease.retries, 1);
+        assert_eq!(sources.netease.timeout, default_source_timeout());
+        assert_eq!(
+            sources.kugou.headers.get("Cookie").map(String::as_str),
+            Some("a=b")
+        );
+        assert_eq!(sources.local.dirs, ["~/Music/Lyrics"]);
+        assert!(sources.embedded.write);
+        assert_eq!(sources.command.command.as_deref(), Some("/bin/provider"));
+        assert_eq!(sources.command.args, ["--lrc"]);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for toml in [
+            "[netease]\ndirs = [\"~/Lyrics\"]",
+            "[qq]\ncommand = \"/bin/provider\"",
+            "[local]\nbase_url = \"http://127.0.0.1\"",
+            "[embedded]\ntimeout = 3",
+            "[command]\nwrite = true",
+            "[kugou]\ntimeuot = 3",
+            "[netese]\nenabled = false",
+            "qq = \"yes\"",
+        ] {
+            assert!(parse(toml).is_err(), "{toml}");
+        }
+    }
+
+    #[test]
+    fn unknown_source_key_names_the_field() {
+        let err = parse("[netease]\ndirs = []").unwrap_err();
+
+        assert!(err.to_string().contains("unknown field `dirs`"), "{err}");
+    }
+}
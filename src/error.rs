@@ -17,9 +17,21 @@ pub enum LyricsError {
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("Tag read error: {0}")]
+    TagReadError(#[from] lofty::error::FileParseError),
+
+    #[error("Tag write error: {0}")]
+    TagWriteError(#[from] lofty::error::FileEncodingError),
+
     #[error("base64 error: {0}")]
     DecodeError(#[from] base64::DecodeError),
 
+    #[error("Command exited with {status}: {stderr}")]
+    CommandFailed {
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+
     #[error("No active media player found")]
     NoPlayerFound,
 
@@ -29,6 +41,9 @@ pub enum LyricsError {
     #[error("No lyrics found")]
     NoLyricsFound,
 
+    #[error("No lyrics found (cached)")]
+    NoLyricsCached,
+
     #[error("JSON parse error")]
     JsonError,
 
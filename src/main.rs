@@ -3,7 +3,8 @@ use std::{fs::OpenOptions, path::PathBuf};
 
 use anyhow::Result;
 use chrono::Local;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use lyrics_next::cache::CacheManager;
 use lyrics_next::client::get_lyrics_client;
 use lyrics_next::config::{Config, log_path};
 use lyrics_next::ui::App;
@@ -14,6 +15,23 @@ struct Args {
     #[arg(short, long)]
     config: Option<PathBuf>,
     // line
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 管理歌词缓存
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// 按配置的大小与时间上限清理缓存
+    Gc,
 }
 
 pub fn init_logger() -> Result<()> {
@@ -56,9 +74,28 @@ async fn main() -> Result<()> {
     log::info!("Starting lyric application...");
     let args = Args::parse();
     Config::load_or_default(args.config)?;
-    get_lyrics_client();
+
+    if let Some(Command::Cache {
+        action: CacheAction::Gc,
+    }) = args.command
+    {
+        let report = CacheManager::new().gc().await?;
+        println!(
+            "Removed {} songs, freed {:.1} MB, cache size {:.1} MB",
+            report.removed,
+            report.freed as f64 / 1024.0 / 1024.0,
+            report.size as f64 / 1024.0 / 1024.0
+        );
+        return Ok(());
+    }
+
     let mut terminal = ratatui::init();
     let app_result = App::default().run(&mut terminal).await;
     ratatui::restore();
+
+    // 退出时清理缓存，同时写入最近访问时间，不与获取歌词并发
+    if let Err(e) = get_lyrics_client().cache.gc().await {
+        log::error!("Cache gc failed: {}", e);
+    }
     app_result
 }
@@ -5,6 +5,16 @@ use mpris::{Player, PlayerFinder, TrackID};
 
 use crate::{config::get_config, error::LyricsError, utils::normalize_text};
 
+mod krc;
+mod qrc;
+mod sylt;
+mod yrc;
+
+pub use krc::KrcParser;
+pub use qrc::QrcParser;
+pub use sylt::SyltParser;
+pub use yrc::YrcParser;
+
 /// 歌曲信息
 #[derive(Debug, Clone, PartialEq)]
 pub struct SongInfo {
@@ -16,6 +26,8 @@ pub struct SongInfo {
     pub album: String,
     /// 时长
     pub duration: f64,
+    /// 文件地址 `xesam:url`，本地文件为 `file://` 开头
+    pub url: String,
 }
 
 impl Default for SongInfo {
@@ -26,6 +38,7 @@ impl Default for SongInfo {
             artist: Default::default(),
             album: Default::default(),
             duration: Default::default(),
+            url: Default::default(),
         }
     }
 }
@@ -58,11 +71,7 @@ fn is_valid_player(player: &Player) -> bool {
     }
 
     if !config.only.is_empty() {
-        if config.only.iter().any(|k| identity.contains(k)) {
-            return true;
-        } else {
-            return false;
-        }
+        return config.only.iter().any(|k| identity.contains(k));
     }
 
     true
@@ -94,6 +103,7 @@ pub async fn get_current_song() -> Result<SongInfo, LyricsError> {
     let artist = metadata.artists().map(|a| a.join(", ")).context("无作家")?;
     let album = metadata.album_name().unwrap_or_default().to_string();
     let duration = metadata.length().map(|d| d.as_secs_f64()).unwrap_or(0.0);
+    let url = metadata.url().unwrap_or_default().to_string();
 
     Ok(SongInfo {
         track_id,
@@ -101,6 +111,7 @@ pub async fn get_current_song() -> Result<SongInfo, LyricsError> {
         artist,
         album,
         duration,
+        url,
     })
 }
 
@@ -184,23 +195,398 @@ pub struct LyricsLine {
     pub timestamp_start: f64, // 单位：秒
     pub timestamp_end: f64,   // 单位：秒
     pub text: String,
+    /// 逐字时间，来自 enhanced LRC 的 `<mm:ss.xx>` 标签
+    pub words: Option<Vec<LyricsWord>>,
+    /// 翻译
+    pub translation: Option<String>,
+    /// 音译 (罗马音/拼音)
+    pub romanization: Option<String>,
+}
+
+/// 逐字歌词片段
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricsWord {
+    pub timestamp_start: f64, // 单位：秒
+    pub timestamp_end: f64,   // 单位：秒
+    pub text: String,
+}
+
+/// 单行解析结果
+#[derive(Debug, Clone, Default)]
+struct ParsedLine {
+    time_tags: Vec<f64>,
+    text: String,
+    /// 逐字片段 (开始时间, 文本)，开始时间为空时沿用行开始时间
+    words: Vec<(Option<f64>, String)>,
+    /// 行尾的结束标签
+    words_end: Option<f64>,
+}
+
+/// 歌词元信息，来自 `[ar:...]` 等标签
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LyricsMetadata {
+    /// `[ti:]` 标题
+    pub title: Option<String>,
+    /// `[ar:]` 作者
+    pub artist: Option<String>,
+    /// `[al:]` 专辑
+    pub album: Option<String>,
+    /// `[by:]` 歌词制作者
+    pub by: Option<String>,
+    /// `[length:]` 时长，单位：秒
+    pub length: Option<f64>,
+    /// `[offset:]` 偏移，单位：毫秒，正数表示歌词提前
+    pub offset: i64,
+    /// 其他未识别的标签
+    pub extra: Vec<(String, String)>,
+}
+
+impl LyricsMetadata {
+    async fn insert(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        match key.to_lowercase().as_str() {
+            "ti" => self.title = Some(value.to_string()),
+            "ar" => self.artist = Some(value.to_string()),
+            "al" => self.album = Some(value.to_string()),
+            "by" => self.by = Some(value.to_string()),
+            "length" => {
+                self.length = match value.contains(':') {
+                    true => LyricParser::parse_time(value).await,
+                    false => value.parse::<f64>().ok(),
+                }
+            }
+            "offset" => {
+                self.offset = value
+                    .trim_start_matches('+')
+                    .parse::<i64>()
+                    .unwrap_or_default()
+            }
+            _ => self.extra.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /// 转换为 LRC 标签行，偏移已应用到时间上因此不输出
+    fn to_lrc(&self) -> Vec<String> {
+        let mut tags = Vec::new();
+        let fields = [
+            ("ti", &self.title),
+            ("ar", &self.artist),
+            ("al", &self.album),
+            ("by", &self.by),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                tags.push(format!("[{key}:{value}]"));
+            }
+        }
+        if let Some(length) = self.length {
+            tags.push(format!("[length:{}]", format_time(length)));
+        }
+        tags
+    }
+}
+
+/// 逐字格式 (KRC/QRC/YRC/SYLT) 的歌词行 (行开始, 行时长, 逐字片段)，单位：秒
+type TimedLine = (f64, f64, Vec<LyricsWord>);
+
+/// 解析后的歌词文档
+#[derive(Debug, Clone, Default)]
+pub struct LyricsDocument {
+    pub metadata: LyricsMetadata,
+    /// 已应用 `[offset:]` 的歌词行
+    pub lines: Vec<LyricsLine>,
+    /// 是否带时间，无时间的纯文本歌词各行时间均为 0
+    pub synced: bool,
+}
+
+impl LyricsDocument {
+    /// 翻译与原文行时间的最大误差，单位：秒
+    const MERGE_TOLERANCE: f64 = 0.3;
+
+    /// 由逐字格式的歌词行构建文档，并应用偏移
+    fn from_timed(
+        mut metadata: LyricsMetadata,
+        entries: Vec<TimedLine>,
+    ) -> Result<Self, LyricsError> {
+        let offset = metadata.offset as f64 / 1000.0;
+        metadata.offset = 0;
+
+        let mut lines = Vec::with_capacity(entries.len());
+        for (i, (start, duration, words)) in entries.iter().enumerate() {
+            let end = entries
+                .get(i + 1)
+                .map(|(next_start, _, _)| *next_start)
+                .unwrap_or(start + duration);
+
+            let words: Vec<LyricsWord> = words
+                .iter()
+                .map(|word| LyricsWord {
+                    timestamp_start: (word.timestamp_start - offset).max(0.0),
+                    timestamp_end: (word.timestamp_end - offset).max(0.0),
+                    text: word.text.clone(),
+                })
+                .collect();
+
+            lines.push(LyricsLine {
+                timestamp_start: (start - offset).max(0.0),
+                timestamp_end: (end - offset).max(0.0),
+                text: words.iter().map(|w| w.text.as_str()).collect::<String>(),
+                words: Some(words),
+                translation: None,
+                romanization: None,
+            });
+        }
+
+        if lines.is_empty() {
+            return Err(LyricsError::EmptyLyrics);
+        }
+
+        Ok(Self {
+            metadata,
+            lines,
+            synced: true,
+        })
+    }
+
+    /// 由无时间的纯文本行构建文档，去掉首尾空行
+    fn from_plain(metadata: LyricsMetadata, texts: Vec<String>) -> Result<Self, LyricsError> {
+        let start = texts.iter().position(|t| !t.is_empty());
+        let end = texts.iter().rposition(|t| !t.is_empty());
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(LyricsError::EmptyLyrics);
+        };
+        let texts = &texts[start..=end];
+        if !Self::is_plain_lyrics(texts) {
+            return Err(LyricsError::LyricsValidationFailed);
+        }
+
+        let lines = texts
+            .iter()
+            .map(|text| LyricsLine {
+                timestamp_start: 0.0,
+                timestamp_end: 0.0,
+                text: text.clone(),
+                words: None,
+                translation: None,
+                romanization: None,
+            })
+            .collect();
+
+        Ok(Self {
+            metadata,
+            lines,
+            synced: false,
+        })
+    }
+
+    /// 排除来源返回的 HTML/XML 错误页与 JSON 错误体，至少需要一行含文字
+    fn is_plain_lyrics(texts: &[String]) -> bool {
+        let body = texts.join("\n");
+        let body = body.trim();
+
+        let markup = body.starts_with('<') && body.ends_with('>');
+        let json = matches!(
+            serde_json::from_str::<serde_json::Value>(body),
+            Ok(serde_json::Value::Object(_) | serde_json::Value::Array(_))
+        );
+        let text = texts
+            .iter()
+            .any(|t| !t.trim_start().starts_with('<') && t.chars().any(char::is_alphabetic));
+
+        !markup && !json && text
+    }
+
+    /// 按时间将翻译歌词合并到对应的原文行
+    pub fn merge_translation(&mut self, translation: &LyricsDocument) {
+        if !self.synced || !translation.synced {
+            return;
+        }
+        for line in self.lines.iter_mut() {
+            line.translation = translation.find_text(line.timestamp_start);
+        }
+    }
+
+    /// 按时间将音译歌词合并到对应的原文行
+    pub fn merge_romanization(&mut self, romanization: &LyricsDocument) {
+        if !self.synced || !romanization.synced {
+            return;
+        }
+        for line in self.lines.iter_mut() {
+            line.romanization = romanization.find_text(line.timestamp_start);
+        }
+    }
+
+    /// 转换为 LRC 文本，逐字时间写为 enhanced LRC 的 `<mm:ss.xx>` 标签
+    pub fn to_lrc(&self) -> String {
+        let mut lrc = self.metadata.to_lrc();
+
+        for line in &self.lines {
+            if !self.synced {
+                lrc.push(line.text.clone());
+                continue;
+            }
+
+            let mut text = format!("[{}]", format_time(line.timestamp_start));
+            match &line.words {
+                Some(words) => {
+                    for (i, word) in words.iter().enumerate() {
+                        text.push_str(&format!(
+                            "<{}>{}",
+                            format_time(word.timestamp_start),
+                            word.text
+                        ));
+                        // 与下一个字不连续时写入结束标签，结束时间未知时不写
+                        let next = words.get(i + 1).map(|w| w.timestamp_start);
+                        if word.timestamp_end > word.timestamp_start
+                            && next.is_none_or(|next| next - word.timestamp_end > 0.01)
+                        {
+                            text.push_str(&format!("<{}>", format_time(word.timestamp_end)));
+                        }
+                    }
+                }
+                None => text.push_str(&line.text),
+            }
+            lrc.push(text);
+        }
+
+        lrc.join("\n")
+    }
+
+    /// 不带时间标签的歌词文本，每行一句
+    pub fn to_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 翻译转换为 LRC 文本
+    pub fn translation_lrc(&self) -> Option<String> {
+        self.track_lrc(|line| line.translation.as_ref())
+    }
+
+    /// 音译转换为 LRC 文本
+    pub fn romanization_lrc(&self) -> Option<String> {
+        self.track_lrc(|line| line.romanization.as_ref())
+    }
+
+    fn track_lrc(&self, track: impl Fn(&LyricsLine) -> Option<&String>) -> Option<String> {
+        let lrc: Vec<String> = self
+            .lines
+            .iter()
+            .filter_map(|line| {
+                track(line).map(|text| format!("[{}]{}", format_time(line.timestamp_start), text))
+            })
+            .collect();
+
+        match lrc.is_empty() {
+            true => None,
+            false => Some(lrc.join("\n")),
+        }
+    }
+
+    /// 查找与时间最接近的非空歌词文本
+    fn find_text(&self, time: f64) -> Option<String> {
+        self.lines
+            .iter()
+            .filter(|line| !line.text.is_empty())
+            .map(|line| ((line.timestamp_start - time).abs(), line))
+            .filter(|(delta, _)| *delta <= Self::MERGE_TOLERANCE)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .map(|(_, line)| line.text.clone())
+    }
+}
+
+/// 逐字格式中的时间标签 (标签开始位置, 标签结束位置, 开始, 时长)，时间单位：秒
+type TimeTag = (usize, usize, f64, f64);
+
+/// 查找 `(开始,时长)` 或 `<开始,时长,0>` 形式的毫秒时间标签，内容不是时间的括号视为文本
+fn find_time_tags(line: &str, open: char, close: char) -> Vec<TimeTag> {
+    let mut tags = Vec::new();
+    let mut search = 0;
+    while let Some(start) = line[search..].find(open).map(|i| search + i) {
+        search = start + open.len_utf8();
+        let Some(end) = line[search..].find(close).map(|i| search + i) else {
+            break;
+        };
+        if let Some((time, duration)) = parse_time_pair(&line[search..end]) {
+            search = end + close.len_utf8();
+            tags.push((start, search, time, duration));
+        }
+    }
+    tags
+}
+
+/// 解析 `开始,时长[,0]` 毫秒时间，返回秒
+fn parse_time_pair(s: &str) -> Option<(f64, f64)> {
+    let mut parts = s.split(',');
+    let start = parts.next()?.trim().parse::<u64>().ok()? as f64 / 1000.0;
+    let duration = parts.next()?.trim().parse::<u64>().ok()? as f64 / 1000.0;
+    if parts.any(|p| p.trim().parse::<i64>().is_err()) {
+        return None;
+    }
+    Some((start, duration))
+}
+
+/// 解析行首的 `[开始,时长]`，返回时间和余下内容
+fn parse_line_time(line: &str) -> Option<(f64, f64, &str)> {
+    let (times, rest) = line.strip_prefix('[')?.split_once(']')?;
+    let (start, duration) = parse_time_pair(times)?;
+    Some((start, duration, rest))
+}
+
+/// 格式化为 LRC 时间 `mm:ss.xx`
+fn format_time(time: f64) -> String {
+    let centis = (time.max(0.0) * 100.0).round() as u64;
+    format!(
+        "{:0>2}:{:0>2}.{:0>2}",
+        centis / 6000,
+        centis % 6000 / 100,
+        centis % 100
+    )
 }
 
 // 解析主逻辑
 pub struct LyricParser;
 
 impl LyricParser {
-    pub async fn parse(doc: String, song_duration: f64) -> Result<Vec<LyricsLine>, LyricsError> {
+    pub async fn parse(doc: String, song_duration: f64) -> Result<LyricsDocument, LyricsError> {
+        let mut metadata = LyricsMetadata::default();
         let mut entries = Vec::new();
+        // 无时间标签的行，全部歌词都没有时间时作为纯文本歌词
+        let mut plain = Vec::new();
 
         // 第一阶段：收集所有时间标签和文本
         for line in doc.lines() {
-            // let line_str = line.to_string();
-            if let Ok((time_tags, text)) = Self::parse_line(&line).await {
-                for ts in time_tags {
-                    entries.push((ts, text.clone()));
+            if let Some((key, value)) = Self::parse_tag(line) {
+                metadata.insert(key, value).await;
+                continue;
+            }
+
+            match Self::parse_line(line).await {
+                Ok(parsed) if parsed.time_tags.is_empty() => plain.push(parsed.text),
+                Ok(parsed) => {
+                    let first = parsed.time_tags[0];
+                    for &ts in &parsed.time_tags {
+                        // 重复行的逐字时间相对第一个标签平移
+                        entries.push((ts, ts - first, parsed.clone()));
+                    }
                 }
-            };
+                // 如 `[Chorus]` 的段落标记
+                Err(_) => plain.push(line.trim().to_string()),
+            }
+        }
+
+        if entries.is_empty() {
+            return LyricsDocument::from_plain(metadata, plain);
+        }
+
+        // 应用偏移
+        let offset = metadata.offset as f64 / 1000.0;
+        for (ts, shift, _) in entries.iter_mut() {
+            *ts = (*ts - offset).max(0.0);
+            *shift -= offset;
         }
 
         // 按时间排序
@@ -208,27 +594,76 @@ impl LyricParser {
 
         // 第二阶段：创建带时间区间的歌词行
         let mut lyrics = Vec::with_capacity(entries.len());
-        for (i, &(start, ref text)) in entries.iter().enumerate() {
+        for (i, (start, shift, parsed)) in entries.iter().enumerate() {
             let end = entries
                 .get(i + 1)
-                .map(|(next_start, _)| *next_start)
+                .map(|(next_start, _, _)| *next_start)
                 .unwrap_or(song_duration);
 
             lyrics.push(LyricsLine {
-                timestamp_start: start,
+                timestamp_start: *start,
                 timestamp_end: end,
-                text: text.clone(),
+                text: parsed.text.clone(),
+                words: Self::build_words(parsed, *start, end, *shift),
+                translation: None,
+                romanization: None,
             });
         }
 
         if lyrics.is_empty() {
             Err(LyricsError::EmptyLyrics)
         } else {
-            Ok(lyrics)
+            Ok(LyricsDocument {
+                metadata,
+                lines: lyrics,
+                synced: true,
+            })
         }
     }
 
-    async fn parse_line(line: &str) -> Result<(Vec<f64>, String), LyricsError> {
+    /// 解析 `[key:value]` 元信息标签行
+    fn parse_tag(line: &str) -> Option<(&str, &str)> {
+        let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+        let (key, value) = inner.split_once(':')?;
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some((key, value))
+    }
+
+    /// 根据行时间补全逐字片段的开始和结束时间
+    fn build_words(
+        parsed: &ParsedLine,
+        line_start: f64,
+        line_end: f64,
+        shift: f64,
+    ) -> Option<Vec<LyricsWord>> {
+        if parsed.words.is_empty() {
+            return None;
+        }
+
+        let starts: Vec<f64> = parsed
+            .words
+            .iter()
+            .map(|(ts, _)| ts.map(|t| t + shift).unwrap_or(line_start))
+            .collect();
+        let last_end = parsed.words_end.map(|t| t + shift).unwrap_or(line_end);
+
+        let words = parsed
+            .words
+            .iter()
+            .enumerate()
+            .map(|(i, (_, text))| LyricsWord {
+                timestamp_start: starts[i],
+                timestamp_end: starts.get(i + 1).copied().unwrap_or(last_end),
+                text: text.clone(),
+            })
+            .collect();
+
+        Some(words)
+    }
+
+    async fn parse_line(line: &str) -> Result<ParsedLine, LyricsError> {
         let mut line = line.trim();
         let mut time_tags = Vec::new();
 
@@ -248,25 +683,458 @@ impl LyricParser {
             }
         }
 
-        // 添加有效歌词行
-        let text = line.trim().to_string();
+        let mut parsed = Self::parse_words(line).await;
+        parsed.time_tags = time_tags;
+
+        Ok(parsed)
+    }
+
+    /// 解析行内 `<mm:ss.xx>` 逐字标签，没有标签时按普通文本处理
+    async fn parse_words(line: &str) -> ParsedLine {
+        let mut words: Vec<(Option<f64>, String)> = Vec::new();
+        let mut current: (Option<f64>, String) = (None, String::new());
+        let mut has_tag = false;
+        let mut rest = line;
+
+        while let Some(open) = rest.find('<') {
+            let Some(close) = rest[open..].find('>').map(|i| open + i) else {
+                break;
+            };
+            let tag = &rest[open + 1..close];
+            match Self::parse_time(tag).await.filter(|_| tag.contains(':')) {
+                Some(time) => {
+                    current.1.push_str(&rest[..open]);
+                    if !current.1.is_empty() {
+                        words.push(current);
+                    }
+                    current = (Some(time), String::new());
+                    has_tag = true;
+                }
+                // 非时间标签，保留为文本
+                None => current.1.push_str(&rest[..=close]),
+            }
+            rest = &rest[close + 1..];
+        }
+        current.1.push_str(rest);
+
+        if !has_tag {
+            return ParsedLine {
+                text: line.trim().to_string(),
+                ..Default::default()
+            };
+        }
+
+        // 最后一个标签后没有文本时作为结束时间
+        let words_end = if current.1.is_empty() {
+            current.0
+        } else {
+            words.push(current);
+            None
+        };
+
+        let text = words
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect::<String>()
+            .trim()
+            .to_string();
 
-        Ok((time_tags, text))
+        ParsedLine {
+            time_tags: Vec::new(),
+            text,
+            words,
+            words_end,
+        }
     }
 
+    /// 解析时间标签，单位：秒
+    ///
+    /// 支持 `mm:ss`、`mm:ss.x`、`mm:ss.xx`、`mm:ss.xxx`、`mm:ss:xx`、
+    /// `hh:mm:ss.xx`、`hh:mm:ss:xx` 以及前导 `-`/`+` 的带符号时间。
+    /// 没有小数点的三段时间总是 `mm:ss:xx`，`hh:mm:ss` 需要带小数
     async fn parse_time(s: &str) -> Option<f64> {
-        let parts: Vec<&str> = s.split(&[':', '.']).collect();
-        if parts.len() < 2 {
+        let s = s.trim();
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(rest) => (-1.0, rest),
+            None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+        };
+
+        let (clock, fraction) = match s.split_once('.') {
+            Some((_, "")) => return None,
+            Some((clock, fraction)) => (clock, Some(fraction)),
+            None => (s, None),
+        };
+        let mut parts: Vec<&str> = clock.split(':').collect();
+
+        // 没有小数点时最后一段为 `mm:ss:xx` 的小数部分
+        let fraction = match fraction {
+            Some(fraction) => fraction,
+            None if parts.len() >= 3 => parts.pop()?,
+            None => "",
+        };
+
+        let (hours, minutes, seconds) = match parts[..] {
+            [m, s] => (0, Self::parse_digits(m)?, Self::parse_digits(s)?),
+            [h, m, s] => (
+                Self::parse_digits(h)?,
+                Self::parse_digits(m)?,
+                Self::parse_digits(s)?,
+            ),
+            _ => return None,
+        };
+
+        // 有小时时分钟不能超过 59
+        if seconds >= 60 || (parts.len() == 3 && minutes >= 60) {
             return None;
         }
 
-        let minutes = parts[0].parse::<f64>().ok()?;
-        let seconds = parts[1].parse::<f64>().ok()?;
-        let millis = parts
-            .get(2)
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.0);
+        let fraction = match fraction.len() {
+            0 => 0.0,
+            1..=3 => Self::parse_digits(fraction)? as f64 / 10f64.powi(fraction.len() as i32),
+            _ => return None,
+        };
+
+        Some(sign * ((hours * 3600 + minutes * 60 + seconds) as f64 + fraction))
+    }
+
+    /// 只允许 ASCII 数字
+    fn parse_digits(s: &str) -> Option<u64> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(start: f64, end: f64, text: &str) -> LyricsWord {
+        LyricsWord {
+            timestamp_start: start,
+            timestamp_end: end,
+            text: text.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn words_take_start_and_end_from_tags() {
+        let parsed = LyricParser::parse_words("<00:01.00>晴<00:01.50>天<00:02.00>").await;
+        assert_eq!(parsed.text, "晴天");
+        assert_eq!(parsed.words_end, Some(2.0));
+
+        let words = LyricParser::build_words(&parsed, 1.0, 5.0, 0.0).unwrap();
+        assert_eq!(words, vec![word(1.0, 1.5, "晴"), word(1.5, 2.0, "天")]);
+    }
+
+    #[tokio::test]
+    async fn last_word_without_end_tag_lasts_until_line_end() {
+        let parsed = LyricParser::parse_words("<00:01.00>故事的<00:02.00>小黄花").await;
+        assert_eq!(parsed.words_end, None);
+
+        let words = LyricParser::build_words(&parsed, 1.0, 5.0, 0.0).unwrap();
+        assert_eq!(
+            words,
+            vec![word(1.0, 2.0, "故事的"), word(2.0, 5.0, "小黄花")]
+        );
+    }
+
+    #[tokio::test]
+    async fn text_before_first_tag_starts_with_line() {
+        let parsed = LyricParser::parse_words("从<00:01.50>前").await;
+        let words = LyricParser::build_words(&parsed, 1.0, 3.0, 0.0).unwrap();
+        assert_eq!(words, vec![word(1.0, 1.5, "从"), word(1.5, 3.0, "前")]);
+    }
+
+    #[tokio::test]
+    async fn non_time_tags_are_kept_as_text() {
+        let parsed = LyricParser::parse_words("<b>晴天</b>").await;
+        assert_eq!(parsed.text, "<b>晴天</b>");
+        assert!(parsed.words.is_empty());
+
+        let parsed = LyricParser::parse_words("<00:01.00>a<b><00:02.00>c").await;
+        assert_eq!(parsed.text, "a<b>c");
+        let words = LyricParser::build_words(&parsed, 1.0, 3.0, 0.0).unwrap();
+        assert_eq!(words, vec![word(1.0, 2.0, "a<b>"), word(2.0, 3.0, "c")]);
+    }
+
+    #[tokio::test]
+    async fn plain_line_has_no_words() {
+        let parsed = LyricParser::parse_words("  晴天  ").await;
+        assert_eq!(parsed.text, "晴天");
+        assert_eq!(LyricParser::build_words(&parsed, 0.0, 1.0, 0.0), None);
+    }
+
+    #[tokio::test]
+    async fn repeated_time_tags_shift_words() {
+        let doc = "[00:01.00][00:11.00]<00:01.00>晴<00:01.50>天<00:02.00>\n".to_string();
+        let lyrics = LyricParser::parse(doc, 20.0).await.unwrap().lines;
+        assert_eq!(lyrics.len(), 2);
+
+        assert_eq!(lyrics[0].timestamp_start, 1.0);
+        assert_eq!(
+            lyrics[0].words,
+            Some(vec![word(1.0, 1.5, "晴"), word(1.5, 2.0, "天")])
+        );
+        assert_eq!(lyrics[1].timestamp_start, 11.0);
+        assert_eq!(lyrics[1].timestamp_end, 20.0);
+        assert_eq!(
+            lyrics[1].words,
+            Some(vec![word(11.0, 11.5, "晴"), word(11.5, 12.0, "天")])
+        );
+    }
+
+    #[tokio::test]
+    async fn metadata_tags_are_collected() {
+        let doc = "[ti:晴天]\n[ar:周杰伦]\n[al:叶惠美]\n[by:someone]\n[re:editor]\n[00:01.00]故事的小黄花\n";
+        let doc = LyricParser::parse(doc.to_string(), 10.0).await.unwrap();
+        assert_eq!(
+            doc.metadata,
+            LyricsMetadata {
+                title: Some("晴天".into()),
+                artist: Some("周杰伦".into()),
+                album: Some("叶惠美".into()),
+                by: Some("someone".into()),
+                extra: vec![("re".into(), "editor".into())],
+                ..Default::default()
+            }
+        );
+        assert_eq!(doc.lines.len(), 1);
+        assert_eq!(doc.lines[0].text, "故事的小黄花");
+    }
+
+    #[tokio::test]
+    async fn positive_offset_moves_lines_and_words_earlier() {
+        let doc = "[offset:+500]\n[00:02.00]<00:02.00>晴<00:02.50>天<00:03.00>\n[00:04.00]故事\n";
+        let doc = LyricParser::parse(doc.to_string(), 10.0).await.unwrap();
+        assert_eq!(doc.metadata.offset, 500);
+
+        let line = &doc.lines[0];
+        assert_eq!((line.timestamp_start, line.timestamp_end), (1.5, 3.5));
+        assert_eq!(
+            line.words,
+            Some(vec![word(1.5, 2.0, "晴"), word(2.0, 2.5, "天")])
+        );
+        assert_eq!(doc.lines[1].timestamp_start, 3.5);
+    }
+
+    #[tokio::test]
+    async fn negative_offset_moves_lines_and_words_later() {
+        let doc = "[offset:-500]\n[00:02.00]<00:02.00>晴<00:02.50>天<00:03.00>\n";
+        let doc = LyricParser::parse(doc.to_string(), 10.0).await.unwrap();
+        assert_eq!(doc.metadata.offset, -500);
+
+        let line = &doc.lines[0];
+        assert_eq!(line.timestamp_start, 2.5);
+        assert_eq!(
+            line.words,
+            Some(vec![word(2.5, 3.0, "晴"), word(3.0, 3.5, "天")])
+        );
+    }
+
+    async fn translated(original: &str, translation: &str) -> Vec<Option<String>> {
+        let mut doc = LyricParser::parse(original.to_string(), 60.0)
+            .await
+            .unwrap();
+        let translation = LyricParser::parse(translation.to_string(), 60.0)
+            .await
+            .unwrap();
+        doc.merge_translation(&translation);
+        doc.lines.into_iter().map(|line| line.translation).collect()
+    }
+
+    #[tokio::test]
+    async fn translation_matches_exact_time() {
+        let merged = translated("[00:01.00]a\n[00:05.00]b\n", "[00:01.00]甲\n[00:05.00]乙\n").await;
+        assert_eq!(merged, vec![Some("甲".into()), Some("乙".into())]);
+    }
+
+    #[tokio::test]
+    async fn translation_matches_within_tolerance() {
+        // 容差内取最接近的一行
+        let merged = translated(
+            "[00:01.00]a\n[00:05.00]b\n",
+            "[00:00.90]甲\n[00:01.20]丙\n[00:05.20]乙\n",
+        )
+        .await;
+        assert_eq!(merged, vec![Some("甲".into()), Some("乙".into())]);
+    }
+
+    #[tokio::test]
+    async fn translation_outside_tolerance_is_dropped() {
+        let merged = translated("[00:01.00]a\n[00:05.00]b\n", "[00:01.50]甲\n[00:04.00]乙\n").await;
+        assert_eq!(merged, vec![None, None]);
+    }
+
+    #[tokio::test]
+    async fn unmatched_translation_lines_are_ignored() {
+        let merged = translated(
+            "[00:01.00]a\n[00:05.00]b\n",
+            "[00:00.00]译者\n[00:01.00]\n[00:01.10]甲\n[00:03.00]多余\n",
+        )
+        .await;
+        // 空翻译行不参与匹配，未匹配的翻译行不产生新行
+        assert_eq!(merged, vec![Some("甲".into()), None]);
+    }
+
+    async fn time(s: &str) -> Option<f64> {
+        // 避免浮点误差，精确到微秒比较
+        LyricParser::parse_time(s)
+            .await
+            .map(|t| (t * 1_000_000.0).round() / 1_000_000.0)
+    }
+
+    #[tokio::test]
+    async fn parse_time_table() {
+        let cases = [
+            // mm:ss 与 1-3 位小数
+            ("00:00", Some(0.0)),
+            ("01:02", Some(62.0)),
+            ("1:2", Some(62.0)),
+            ("01:02.3", Some(62.3)),
+            ("01:02.34", Some(62.34)),
+            ("01:02.345", Some(62.345)),
+            ("01:02.050", Some(62.05)),
+            ("75:00.00", Some(4500.0)),
+            // 没有小数点的三段时间为 mm:ss:xx
+            ("00:02:03", Some(2.03)),
+            ("01:02:03", Some(62.03)),
+            ("01:02:3", Some(62.3)),
+            ("01:02:345", Some(62.345)),
+            ("01:02:75", Some(62.75)),
+            ("00:12:99", Some(12.99)),
+            // hh:mm:ss 带小数
+            ("01:02:03.5", Some(3723.5)),
+            ("01:02:03.456", Some(3723.456)),
+            ("01:02:03:45", Some(3723.45)),
+            // 带符号
+            ("-00:01.50", Some(-1.5)),
+            ("+00:01.50", Some(1.5)),
+            ("-01:00:00.00", Some(-3600.0)),
+            (" 00:01.50 ", Some(1.5)),
+            // 格式错误
+            ("", None),
+            ("1", None),
+            ("12.5", None),
+            ("aa:bb", None),
+            ("01:60", None),
+            ("01:60:00", None),
+            ("01:60:00.00", None),
+            ("01:02:60.00", None),
+            ("01:02.", None),
+            ("01:02.x", None),
+            ("01:02.3456", None),
+            ("01:02:3456", None),
+            ("01:02:03:04:05", None),
+            ("01::02", None),
+            ("--01:02", None),
+            ("+-01:02", None),
+            ("01:-2", None),
+            ("01:02.-3", None),
+            ("01:02.+3", None),
+            ("０１:０２", None),
+            ("01 : 02", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(time(input).await, expected, "{input:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn offset_tag_is_signed() {
+        let cases = [
+            ("500", 500),
+            ("+250", 250),
+            ("-300", -300),
+            (" -40 ", -40),
+            ("abc", 0),
+        ];
+
+        for (value, expected) in cases {
+            let mut metadata = LyricsMetadata::default();
+            metadata.insert("offset", value).await;
+            assert_eq!(metadata.offset, expected, "{value:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn centisecond_colon_document_keeps_order() {
+        let doc =
+            "[00:01:50]第一行\n[00:02:03]第二行\n[00:02:75]第三行\n[00:03:00]第四行".to_string();
+
+        let doc = LyricParser::parse(doc, 10.0).await.unwrap();
+
+        let lines: Vec<(f64, &str)> = doc
+            .lines
+            .iter()
+            .map(|l| ((l.timestamp_start * 100.0).round() / 100.0, l.text.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (1.5, "第一行"),
+                (2.03, "第二行"),
+                (2.75, "第三行"),
+                (3.0, "第四行")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_time_tags_are_not_timed() {
+        let doc = "[00:01.00]第一行\n[01:60.00]坏标签\n[00:02.00]第二行".to_string();
+
+        let doc = LyricParser::parse(doc, 10.0).await.unwrap();
+
+        let texts: Vec<&str> = doc.lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["第一行", "第二行"]);
+        assert_eq!(doc.lines[0].timestamp_end, 2.0);
+    }
+
+    #[tokio::test]
+    async fn untimed_text_is_plain_lyrics() {
+        let doc = "\n[Chorus]\n故事的小黄花\n\nLook at the stars\n\n".to_string();
+
+        let doc = LyricParser::parse(doc, 0.0).await.unwrap();
+
+        assert!(!doc.synced);
+        let texts: Vec<&str> = doc.lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["[Chorus]", "故事的小黄花", "", "Look at the stars"]);
+    }
+
+    #[tokio::test]
+    async fn error_bodies_are_not_plain_lyrics() {
+        let cases = [
+            "<!DOCTYPE html>\n<html><body><h1>502 Bad Gateway</h1></body></html>",
+            "<?xml version=\"1.0\"?>\n<error code=\"404\">not found</error>",
+            "<html>\n<head><title>Error</title></head>\n</html>",
+            r#"{"error": "No lyrics found"}"#,
+            r#"[{"code": 500}]"#,
+            "...\n---\n123",
+        ];
+
+        for body in cases {
+            let err = LyricParser::parse(body.to_string(), 0.0).await.unwrap_err();
+            assert!(
+                matches!(err, LyricsError::LyricsValidationFailed),
+                "{body:?}: {err}"
+            );
+        }
+        let err = LyricParser::parse("\n \n".to_string(), 0.0)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LyricsError::EmptyLyrics), "{err}");
+    }
+
+    #[tokio::test]
+    async fn hours_form_sorts_after_minutes() {
+        let doc = "[01:00:00.00]一小时\n[59:59.99]之前".to_string();
+
+        let doc = LyricParser::parse(doc, 0.0).await.unwrap();
 
-        Some(minutes * 60.0 + seconds + millis / 100.0)
+        assert_eq!(doc.lines[0].text, "之前");
+        assert_eq!(doc.lines[1].timestamp_start, 3600.0);
     }
 }
@@ -0,0 +1,167 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use serde::Deserialize;
+
+use super::{
+    LyricParser, LyricsDocument, LyricsLine, LyricsMetadata, LyricsWord, TimedLine, find_time_tags,
+    parse_line_time,
+};
+use crate::error::LyricsError;
+
+/// `[language:]` 标签内容，base64 编码的 JSON
+#[derive(Debug, Deserialize)]
+struct Language {
+    content: Vec<LanguageContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageContent {
+    /// 0 音译，1 翻译
+    #[serde(rename = "type")]
+    kind: u8,
+    /// 按歌词行顺序排列，音译为逐字列表
+    #[serde(rename = "lyricContent")]
+    lyric_content: Vec<Vec<String>>,
+}
+
+// 酷狗 KRC 逐字歌词解析
+pub struct KrcParser;
+
+impl KrcParser {
+    /// 解析解密后的 KRC 文本，行格式为 `[开始,时长]<偏移,时长,0>字...`，时间单位毫秒
+    pub async fn parse(doc: &str) -> Result<LyricsDocument, LyricsError> {
+        let mut metadata = LyricsMetadata::default();
+        let mut language = None;
+        let mut entries = Vec::new();
+
+        for line in doc.lines() {
+            let line = line.trim();
+            if let Some((key, value)) = LyricParser::parse_tag(line) {
+                match key {
+                    "language" => language = Some(value.trim().to_string()),
+                    _ => metadata.insert(key, value).await,
+                }
+                continue;
+            }
+
+            if let Some(entry) = Self::parse_line(line) {
+                entries.push(entry);
+            }
+        }
+
+        let mut doc = LyricsDocument::from_timed(metadata, entries)?;
+        if let Some(language) = language {
+            Self::merge_language(&mut doc.lines, &language);
+        }
+
+        Ok(doc)
+    }
+
+    fn parse_line(line: &str) -> Option<TimedLine> {
+        let (start, duration, rest) = parse_line_time(line)?;
+
+        // 字的时间相对行开始，文本在标签之后
+        let tags = find_time_tags(rest, '<', '>');
+        let words = tags
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, tag_end, offset, word_duration))| LyricsWord {
+                timestamp_start: start + offset,
+                timestamp_end: start + offset + word_duration,
+                text: rest[tag_end..tags.get(i + 1).map_or(rest.len(), |t| t.0)].to_string(),
+            })
+            .collect();
+
+        Some((start, duration, words))
+    }
+
+    /// 合并 `[language:]` 中的翻译与音译
+    fn merge_language(lines: &mut [LyricsLine], language: &str) {
+        let language = BASE64_STANDARD
+            .decode(language)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Language>(&bytes).ok());
+        let Some(language) = language else {
+            log::warn!("KRC language tag decode failed");
+            return;
+        };
+
+        for content in language.content {
+            for (line, texts) in lines.iter_mut().zip(content.lyric_content) {
+                let text = texts.concat();
+                if text.trim().is_empty() {
+                    continue;
+                }
+                match content.kind {
+                    0 => line.romanization = Some(text),
+                    1 => line.translation = Some(text),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `{"content":[{"type":1,"lyricContent":[["Line one"],[""]]},{"type":0,"lyricContent":[["gu ","shi"],["de"]]}]}`
+    const LANGUAGE: &str = "eyJjb250ZW50IjpbeyJ0eXBlIjoxLCJseXJpY0NvbnRlbnQiOltbIkxpbmUgb25lIl0sWyIiXV19LHsidHlwZSI6MCwibHlyaWNDb250ZW50IjpbWyJndSAiLCJzaGkiXSxbImRlIl1dfV19";
+
+    #[test]
+    fn parse_line_reads_word_offsets() {
+        let (start, duration, words) =
+            KrcParser::parse_line("[1000,3000]<0,1000,0>故事<1000,500,0>的").unwrap();
+
+        assert_eq!((start, duration), (1.0, 3.0));
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "故事");
+        assert_eq!(
+            (words[0].timestamp_start, words[0].timestamp_end),
+            (1.0, 2.0)
+        );
+        assert_eq!(
+            (words[1].timestamp_start, words[1].timestamp_end),
+            (2.0, 2.5)
+        );
+    }
+
+    #[test]
+    fn parse_line_rejects_malformed() {
+        for line in ["", "故事", "[1000]<0,1000,0>故事", "[a,b]故事"] {
+            assert!(KrcParser::parse_line(line).is_none(), "{line}");
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_applies_offset_and_language() {
+        let doc = format!(
+            "[ti:晴天]\n[offset:500]\n[language:{LANGUAGE}]\n\
+             [1000,3000]<0,1000,0>故事<1000,2000,0>的\n\
+             [4000,1000]<0,1000,0>小黄花\n"
+        );
+
+        let doc = KrcParser::parse(&doc).await.unwrap();
+
+        assert_eq!(doc.metadata.offset, 0);
+        assert_eq!(doc.lines.len(), 2);
+        let line = &doc.lines[0];
+        assert_eq!(line.text, "故事的");
+        assert_eq!((line.timestamp_start, line.timestamp_end), (0.5, 3.5));
+        assert_eq!(line.words.as_ref().unwrap()[1].timestamp_start, 1.5);
+        assert_eq!(line.translation.as_deref(), Some("Line one"));
+        assert_eq!(line.romanization.as_deref(), Some("gu shi"));
+        // 最后一行持续到自身时长结束，空翻译不保留
+        assert_eq!(doc.lines[1].timestamp_end, 4.5);
+        assert_eq!(doc.lines[1].translation, None);
+        assert_eq!(doc.lines[1].romanization.as_deref(), Some("de"));
+    }
+
+    #[tokio::test]
+    async fn parse_without_lines_is_empty() {
+        let err = KrcParser::parse("[ti:晴天]\n[ar:周杰伦]\n")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LyricsError::EmptyLyrics), "{err}");
+    }
+}
@@ -0,0 +1,97 @@
+use super::{
+    LyricParser, LyricsDocument, LyricsMetadata, LyricsWord, TimedLine, find_time_tags,
+    parse_line_time,
+};
+use crate::error::LyricsError;
+
+// QQ 音乐 QRC 逐字歌词解析
+pub struct QrcParser;
+
+impl QrcParser {
+    /// 解析解密后的 QRC，可以是 XML 包装或 `LyricContent` 中的歌词内容，
+    /// 行格式为 `[开始,时长]字(开始,时长)...`，时间单位毫秒
+    pub async fn parse(doc: &str) -> Result<LyricsDocument, LyricsError> {
+        let content = Self::extract_content(doc).unwrap_or_else(|| doc.to_string());
+
+        let mut metadata = LyricsMetadata::default();
+        let mut entries = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some((key, value)) = LyricParser::parse_tag(line) {
+                metadata.insert(key, value).await;
+                continue;
+            }
+
+            if let Some(entry) = Self::parse_line(line) {
+                entries.push(entry);
+            }
+        }
+
+        LyricsDocument::from_timed(metadata, entries)
+    }
+
+    /// 提取 XML 中 `LyricContent` 属性
+    fn extract_content(doc: &str) -> Option<String> {
+        let start = doc.find("LyricContent=\"")? + "LyricContent=\"".len();
+        let end = start + doc[start..].find('"')?;
+        Some(unescape_xml(&doc[start..end]))
+    }
+
+    fn parse_line(line: &str) -> Option<TimedLine> {
+        let (start, duration, rest) = parse_line_time(line)?;
+
+        // 文本在标签之前
+        let tags = find_time_tags(rest, '(', ')');
+        let words = tags
+            .iter()
+            .enumerate()
+            .map(
+                |(i, &(tag_start, _, word_start, word_duration))| LyricsWord {
+                    timestamp_start: word_start,
+                    timestamp_end: word_start + word_duration,
+                    text: rest[i.checked_sub(1).map_or(0, |p| tags[p].1)..tag_start].to_string(),
+                },
+            )
+            .collect();
+
+        Some((start, duration, words))
+    }
+}
+
+/// XML 属性值反转义
+fn unescape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let decoded = match &rest[1..semi] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
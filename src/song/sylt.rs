@@ -0,0 +1,155 @@
+use super::{LyricsDocument, LyricsLine, LyricsMetadata, LyricsWord, TimedLine};
+use crate::error::LyricsError;
+
+// ID3v2 SYLT 同步歌词解析与生成
+pub struct SyltParser;
+
+impl SyltParser {
+    /// 解析 SYLT 的 (时间, 文本) 列表，时间单位毫秒。
+    /// 以换行开头的文本开始新的一行，此时每项为一个字；没有换行时每项为一行。
+    /// 最后一项持续到 `song_duration`，时长未知时不设结束时间
+    pub fn parse(
+        content: &[(u32, String)],
+        song_duration: f64,
+    ) -> Result<LyricsDocument, LyricsError> {
+        let split = content.iter().skip(1).any(|(_, text)| starts_line(text));
+
+        let mut groups: Vec<Vec<LyricsWord>> = Vec::new();
+        for (i, (time, text)) in content.iter().enumerate() {
+            let start = *time as f64 / 1000.0;
+            // 字持续到下一项开始，结束时间等于开始时间表示未知
+            let end = content
+                .get(i + 1)
+                .map_or(song_duration, |(next, _)| *next as f64 / 1000.0);
+            let word = LyricsWord {
+                timestamp_start: start,
+                timestamp_end: end.max(start),
+                text: text.trim_start_matches(['\r', '\n']).to_string(),
+            };
+            // 空文本只用于标记上一个字的结束
+            if split && word.text.is_empty() {
+                continue;
+            }
+            match groups.last_mut() {
+                Some(words) if split && !starts_line(text) => words.push(word),
+                _ => groups.push(vec![word]),
+            }
+        }
+
+        // 每行只有一项时为逐行歌词，不保留逐字时间
+        if groups.iter().all(|words| words.len() == 1) {
+            return Self::from_lines(groups.into_iter().flatten().collect());
+        }
+
+        let entries: Vec<TimedLine> = groups
+            .into_iter()
+            .map(|words| {
+                let start = words[0].timestamp_start;
+                let end = words.last().map_or(start, |w| w.timestamp_end);
+                (start, end - start, words)
+            })
+            .collect();
+        LyricsDocument::from_timed(LyricsMetadata::default(), entries)
+    }
+
+    /// 转换为 SYLT 的 (时间, 文本) 列表，有逐字时间时每个字为一项，除第一行外以换行开头
+    pub fn encode(doc: &LyricsDocument) -> Vec<(u32, String)> {
+        if !doc.synced {
+            return Vec::new();
+        }
+
+        let ms = |time: f64| (time * 1000.0).round() as u32;
+        let mut content = Vec::new();
+        for (i, line) in doc.lines.iter().enumerate() {
+            let newline = if i == 0 { "" } else { "\n" };
+            match line.words.as_deref() {
+                Some([first, rest @ ..]) => {
+                    content.push((
+                        ms(first.timestamp_start),
+                        format!("{newline}{}", first.text),
+                    ));
+                    content.extend(rest.iter().map(|w| (ms(w.timestamp_start), w.text.clone())));
+                }
+                _ => content.push((ms(line.timestamp_start), format!("{newline}{}", line.text))),
+            }
+        }
+        content
+    }
+
+    fn from_lines(entries: Vec<LyricsWord>) -> Result<LyricsDocument, LyricsError> {
+        if entries.iter().all(|e| e.text.trim().is_empty()) {
+            return Err(LyricsError::EmptyLyrics);
+        }
+
+        let lines = entries
+            .into_iter()
+            .map(|entry| LyricsLine {
+                timestamp_start: entry.timestamp_start,
+                timestamp_end: entry.timestamp_end,
+                text: entry.text,
+                words: None,
+                translation: None,
+                romanization: None,
+            })
+            .collect();
+
+        Ok(LyricsDocument {
+            metadata: LyricsMetadata::default(),
+            lines,
+            synced: true,
+        })
+    }
+}
+
+fn starts_line(text: &str) -> bool {
+    text.starts_with(['\r', '\n'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(items: &[(u32, &str)]) -> Vec<(u32, String)> {
+        items.iter().map(|(t, s)| (*t, s.to_string())).collect()
+    }
+
+    #[test]
+    fn last_line_lasts_until_song_end() {
+        let doc = SyltParser::parse(&content(&[(1000, "故事"), (4500, "小黄花")]), 10.0).unwrap();
+
+        assert_eq!(doc.lines[0].timestamp_end, 4.5);
+        assert_eq!(doc.lines[1].timestamp_end, 10.0);
+    }
+
+    #[test]
+    fn last_word_lasts_until_song_end() {
+        let items = content(&[(1000, "故事"), (1500, "的"), (2000, "\n小黄花")]);
+        let doc = SyltParser::parse(&items, 10.0).unwrap();
+
+        let last = doc.lines[1].words.as_ref().unwrap();
+        assert_eq!(
+            (last[0].timestamp_start, last[0].timestamp_end),
+            (2.0, 10.0)
+        );
+        assert_eq!(doc.lines[1].timestamp_end, 10.0);
+    }
+
+    #[test]
+    fn unknown_song_end_is_left_open() {
+        let items = content(&[(1000, "故事"), (1500, "的"), (2000, "\n小黄花")]);
+        let doc = SyltParser::parse(&items, 0.0).unwrap();
+
+        assert_eq!(
+            doc.to_lrc(),
+            "[00:01.00]<00:01.00>故事<00:01.50>的<00:02.00>\n[00:02.00]<00:02.00>小黄花"
+        );
+    }
+
+    #[test]
+    fn end_marker_closes_last_word() {
+        let items = content(&[(1000, "故事"), (2000, "\n小黄花"), (3000, "")]);
+        let doc = SyltParser::parse(&items, 10.0).unwrap();
+
+        assert_eq!(doc.lines[1].timestamp_end, 3.0);
+    }
+}
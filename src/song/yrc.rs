@@ -0,0 +1,120 @@
+use super::{
+    LyricParser, LyricsDocument, LyricsMetadata, LyricsWord, TimedLine, find_time_tags,
+    parse_line_time,
+};
+use crate::error::LyricsError;
+
+// 网易云 YRC 逐字歌词解析
+pub struct YrcParser;
+
+impl YrcParser {
+    /// 解析 YRC，行格式为 `[开始,时长](开始,时长,0)字...`，时间单位毫秒。
+    /// `{"t":0,"c":[...]}` 形式的作词作曲信息行忽略
+    pub async fn parse(doc: &str) -> Result<LyricsDocument, LyricsError> {
+        let mut metadata = LyricsMetadata::default();
+        let mut entries = Vec::new();
+
+        for line in doc.lines() {
+            let line = line.trim();
+            if let Some((key, value)) = LyricParser::parse_tag(line) {
+                metadata.insert(key, value).await;
+                continue;
+            }
+
+            if let Some(entry) = Self::parse_line(line) {
+                entries.push(entry);
+            }
+        }
+
+        LyricsDocument::from_timed(metadata, entries)
+    }
+
+    fn parse_line(line: &str) -> Option<TimedLine> {
+        let (start, duration, rest) = parse_line_time(line)?;
+
+        // 字的时间为绝对时间，文本在标签之后
+        let tags = find_time_tags(rest, '(', ')');
+        let words = tags
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, tag_end, word_start, word_duration))| LyricsWord {
+                timestamp_start: word_start,
+                timestamp_end: word_start + word_duration,
+                text: rest[tag_end..tags.get(i + 1).map_or(rest.len(), |t| t.0)].to_string(),
+            })
+            .collect();
+
+        Some((start, duration, words))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_line_reads_absolute_word_times() {
+        let (start, duration, words) =
+            YrcParser::parse_line("[1000,3000](1000,1000,0)故事(2000,500,0)的").unwrap();
+
+        assert_eq!((start, duration), (1.0, 3.0));
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "故事");
+        assert_eq!(
+            (words[0].timestamp_start, words[0].timestamp_end),
+            (1.0, 2.0)
+        );
+        assert_eq!(words[1].text, "的");
+        assert_eq!(
+            (words[1].timestamp_start, words[1].timestamp_end),
+            (2.0, 2.5)
+        );
+    }
+
+    #[test]
+    fn parse_line_keeps_non_time_parentheses_as_text() {
+        let (_, _, words) =
+            YrcParser::parse_line("[1000,2000](1000,1000,0)晴天(Live)(2000,1000,0)!").unwrap();
+
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "晴天(Live)");
+        assert_eq!(words[1].text, "!");
+    }
+
+    #[test]
+    fn parse_line_rejects_malformed() {
+        for line in [
+            "",
+            "故事",
+            "[1000](1000,1000,0)故事",
+            "[a,b]故事",
+            r#"{"t":0,"c":[]}"#,
+        ] {
+            assert!(YrcParser::parse_line(line).is_none(), "{line}");
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_skips_info_lines_and_applies_offset() {
+        let doc = "{\"t\":0,\"c\":[{\"tx\":\"作词: \"},{\"tx\":\"周杰伦\"}]}\n\
+                   [offset:500]\n\
+                   [1000,3000](1000,1000,0)故事(2000,2000,0)的\n\
+                   [4000,1000](4000,1000,0)小黄花\n";
+
+        let doc = YrcParser::parse(doc).await.unwrap();
+
+        assert_eq!(doc.metadata.offset, 0);
+        assert_eq!(doc.lines.len(), 2);
+        let line = &doc.lines[0];
+        assert_eq!(line.text, "故事的");
+        assert_eq!((line.timestamp_start, line.timestamp_end), (0.5, 3.5));
+        assert_eq!(line.words.as_ref().unwrap()[1].timestamp_start, 1.5);
+        assert_eq!(doc.lines[1].timestamp_end, 4.5);
+    }
+
+    #[tokio::test]
+    async fn parse_without_lines_is_empty() {
+        let err = YrcParser::parse("{\"t\":0,\"c\":[]}\n").await.unwrap_err();
+        assert!(matches!(err, LyricsError::EmptyLyrics));
+    }
+}
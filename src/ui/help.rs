@@ -29,6 +29,13 @@ impl HelpScreen {
             ("     n | j ", "下一曲"),
             ("     p | k ", "上一曲"),
             ("     s  ", "搜索"),
+            ("     + | = ", "歌词提前 0.1s"),
+            ("         - ", "歌词延后 0.1s"),
+            ("         t ", "显示/隐藏翻译"),
+            ("         r ", "显示/隐藏音译"),
+            ("         e ", "写入歌词到音频文件"),
+            ("         f ", "忽略缓存重新获取"),
+            ("   Up | Down ", "滚动无时间歌词"),
         ];
         help(lines).render(lyric_chunk, buf);
 
@@ -1,11 +1,11 @@
 use std::time::Duration;
 
 use crate::{
-    client::get_lyrics_client,
+    client::{LyricsContent, get_lyrics_client},
     config::get_config,
     error::LyricsError,
     song::{
-        LyricParser, LyricsLine, PlayTime, PlayerAction, SongInfo, get_current_song,
+        LyricParser, LyricsLine, LyricsWord, PlayTime, PlayerAction, SongInfo, get_current_song,
         get_current_time_song, player_action,
     },
 };
@@ -57,10 +57,19 @@ impl LyricsScreen {
     }
 
     fn get_window_title(&self) -> String {
-        match !self.state.song.title.is_empty() {
-            true => self.state.song.title.clone(),
-            false => " No song playing ".into(),
+        let mut title = match (!self.state.song.title.is_empty(), self.state.offset) {
+            (true, 0) => self.state.song.title.clone(),
+            (true, offset) => format!(
+                "{} ({:+.1}s)",
+                self.state.song.title,
+                offset as f64 / 1000.0
+            ),
+            (false, _) => return " No song playing ".into(),
+        };
+        if let Some(source) = &self.state.source {
+            title.push_str(&format!(" [{source}]"));
         }
+        title
     }
 
     pub fn render_title(&self, area: Rect, buf: &mut Buffer) {
@@ -123,37 +132,72 @@ impl LyricsScreen {
             return;
         }
 
+        if let Some(no_lyrics) = state.no_lyrics {
+            Paragraph::new(no_lyrics.message())
+                .style(UNSUNG_WORD_STYLE)
+                .centered()
+                .block(
+                    Block::default()
+                        .title(self.get_window_title())
+                        .borders(Borders::ALL),
+                )
+                .render(area, buf);
+            return;
+        }
+
         // 使用预计算的显示参数
         let metrics = &state.view_metrics;
         let start = state.target_scroll.min(metrics.scroll_range);
         let end = (start + metrics.visible_lines).min(metrics.content_height);
+        let show_translation = state.show_translation();
+        let show_romanization = state.show_romanization();
         let mut lines = Vec::new();
         for (i, line) in state.lyrics[start..end].iter().enumerate() {
-            let is_current = start + i == state.find_current_line().unwrap_or(0);
+            let is_current = state.synced && start + i == state.find_current_line().unwrap_or(0);
 
-            let line_text = match get_config().read().unwrap().ui.time {
+            let time_prefix = match state.synced && get_config().read().unwrap().ui.time {
                 true => format!(
-                    "[{:0>2}:{:0>2}] {}",
+                    "[{:0>2}:{:0>2}] ",
                     (line.timestamp_start / 60.0).floor() as u64,
                     (line.timestamp_start % 60.0).floor() as u64,
-                    line.text
                 ),
-                false => line.text.clone(),
+                false => String::new(),
             };
 
-            let style = if is_current {
-                Style::new()
-                    .fg(Color::Indexed(196))
-                    .bg(Color::Reset)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::new()
-                    .fg(Color::Indexed(94 + i as u8))
-                    .bg(Color::Reset)
+            let line = match (&line.words, is_current) {
+                // 逐字高亮
+                (Some(words), true) => {
+                    let mut spans = vec![Span::styled(time_prefix, CURRENT_LINE_STYLE)];
+                    spans.extend(karaoke_spans(words, state.lyric_time()));
+                    Line::from(spans)
+                }
+                _ => {
+                    let style = if is_current {
+                        CURRENT_LINE_STYLE
+                    } else {
+                        Style::new()
+                            .fg(Color::Indexed(94 + i as u8))
+                            .bg(Color::Reset)
+                    };
+                    Line::styled(format!("{}{}", time_prefix, line.text), style)
+                }
             };
-
-            let line = Line::styled(line_text, style);
             lines.push(line);
+
+            // 无翻译时保留空行，保持滚动对齐
+            let style = match is_current {
+                true => TRANSLATION_CURRENT_STYLE,
+                false => TRANSLATION_STYLE,
+            };
+            let current = &state.lyrics[start + i];
+            if show_translation {
+                let translation = current.translation.clone().unwrap_or_default();
+                lines.push(Line::styled(translation, style));
+            }
+            if show_romanization {
+                let romanization = current.romanization.clone().unwrap_or_default();
+                lines.push(Line::styled(romanization, style));
+            }
         }
 
         let block = Block::default()
@@ -177,6 +221,14 @@ impl LyricsScreen {
             KeyCode::Char('p') | KeyCode::Char('k') => {
                 self.state.action(PlayerAction::Previous).await
             }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.state.shift_offset(OFFSET_STEP).await,
+            KeyCode::Char('-') => self.state.shift_offset(-OFFSET_STEP).await,
+            KeyCode::Char('t') => toggle_translation(),
+            KeyCode::Char('r') => toggle_romanization(),
+            KeyCode::Char('e') => self.state.embed().await,
+            KeyCode::Char('f') => self.state.refetch().await,
+            KeyCode::Up => self.state.scroll(-1),
+            KeyCode::Down => self.state.scroll(1),
             _ => {}
         }
     }
@@ -201,6 +253,98 @@ impl LyricsScreen {
     }
 }
 
+/// 歌词偏移调整步长，单位：毫秒
+const OFFSET_STEP: i64 = 100;
+
+const CURRENT_LINE_STYLE: Style = Style::new()
+    .fg(Color::Indexed(196))
+    .bg(Color::Reset)
+    .add_modifier(Modifier::BOLD);
+
+const UNSUNG_WORD_STYLE: Style = Style::new()
+    .fg(Color::Indexed(250))
+    .bg(Color::Reset)
+    .add_modifier(Modifier::BOLD);
+
+const TRANSLATION_STYLE: Style = Style::new()
+    .fg(Color::Indexed(245))
+    .bg(Color::Reset)
+    .add_modifier(Modifier::ITALIC);
+
+const TRANSLATION_CURRENT_STYLE: Style = Style::new()
+    .fg(Color::Indexed(203))
+    .bg(Color::Reset)
+    .add_modifier(Modifier::ITALIC);
+
+/// 切换翻译显示
+fn toggle_translation() {
+    let mut config = get_config().write().unwrap();
+    config.ui.translation = !config.ui.translation;
+}
+
+/// 切换音译显示
+fn toggle_romanization() {
+    let mut config = get_config().write().unwrap();
+    config.ui.romanization = !config.ui.romanization;
+}
+
+/// 按播放时间逐字着色，正在唱的片段按字符比例拆分
+fn karaoke_spans(words: &[LyricsWord], current_time: f64) -> Vec<Span<'static>> {
+    let mut spans = Vec::with_capacity(words.len() + 1);
+
+    for word in words {
+        if current_time >= word.timestamp_end {
+            spans.push(Span::styled(word.text.clone(), CURRENT_LINE_STYLE));
+        } else if current_time < word.timestamp_start {
+            spans.push(Span::styled(word.text.clone(), UNSUNG_WORD_STYLE));
+        } else {
+            let duration = word.timestamp_end - word.timestamp_start;
+            let chars = word.text.chars().count();
+            let progress = if duration > 0.0 {
+                (current_time - word.timestamp_start) / duration
+            } else {
+                1.0
+            };
+            let sung = ((chars as f64 * progress).floor() as usize).min(chars);
+            let split = word
+                .text
+                .char_indices()
+                .nth(sung)
+                .map(|(idx, _)| idx)
+                .unwrap_or(word.text.len());
+
+            spans.push(Span::styled(
+                word.text[..split].to_string(),
+                CURRENT_LINE_STYLE,
+            ));
+            spans.push(Span::styled(
+                word.text[split..].to_string(),
+                UNSUNG_WORD_STYLE,
+            ));
+        }
+    }
+
+    spans
+}
+
+/// 没有歌词的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoLyrics {
+    /// 本次获取时所有来源都没有歌词
+    NotFound,
+    /// 缓存中记录了没有歌词，未重新获取
+    Cached,
+}
+
+impl NoLyrics {
+    fn message(self) -> &'static str {
+        match self {
+            NoLyrics::NotFound => "No lyrics found, press f to refetch",
+            NoLyrics::Cached => "No lyrics found (cached), press f to refetch",
+        }
+    }
+}
+
 // 新增显示参数结构体
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ViewMetrics {
@@ -231,6 +375,16 @@ pub struct LyricState {
     pub retry_counter: u32,
     /// 进度
     pub progress: f64,
+    /// 用户调整的歌词偏移，单位：毫秒，正数表示歌词提前
+    pub offset: i64,
+    /// 缓存歌词的来源
+    pub source: Option<String>,
+    /// 没有歌词时的原因
+    pub no_lyrics: Option<NoLyrics>,
+    /// 歌词是否带时间
+    pub synced: bool,
+    /// 纯文本歌词是否手动滚动，否则按播放进度滚动
+    pub manual_scroll: bool,
 }
 
 impl LyricState {
@@ -238,7 +392,8 @@ impl LyricState {
     pub fn calculate_metrics(&mut self, area: Size) {
         let content_height = self.lyrics.len();
         let viewport_height = area.height as usize;
-        let visible_lines = viewport_height.saturating_sub(2); // 保留边界空间
+        // 保留边界空间
+        let visible_lines = viewport_height.saturating_sub(2) / self.rows_per_line();
         let scroll_range = content_height.saturating_sub(visible_lines);
 
         self.view_metrics = ViewMetrics {
@@ -280,8 +435,16 @@ impl LyricState {
         if song != self.song {
             self.reset();
             self.song = song.clone();
-            let doc = get_lyrics_client().get_lyrics(&song).await?;
-            self.lyrics = LyricParser::parse(doc, song.duration).await?;
+            let client = get_lyrics_client();
+            self.offset = client.cache.get_offset(&song).await;
+            match client.get_lyrics(&song).await {
+                Ok(content) => self.load(&song, content).await?,
+                // 没有歌词不是错误，不重试，按 f 重新获取
+                Err(LyricsError::NoLyricsFound) => self.no_lyrics = Some(NoLyrics::NotFound),
+                Err(LyricsError::NoLyricsCached) => self.no_lyrics = Some(NoLyrics::Cached),
+                Err(e) => return Err(e),
+            }
+            self.source = client.cache.entry(&song).await.and_then(|e| e.source);
         }
 
         // 获取当前播放进度
@@ -289,7 +452,14 @@ impl LyricState {
         self.progress = self.play_time.current_time / song.duration;
 
         // 更新滚动位置
-        if let Some(pos) = self.find_current_line() {
+        if !self.synced {
+            // 纯文本歌词按播放进度滚动
+            if !self.manual_scroll {
+                let progress = self.progress.clamp(0.0, 1.0);
+                self.target_scroll =
+                    (progress * self.view_metrics.scroll_range as f64).round() as usize;
+            }
+        } else if let Some(pos) = self.find_current_line() {
             let target_offset = pos.saturating_sub(self.view_metrics.visible_lines / 2);
             self.target_scroll = target_offset.min(self.view_metrics.scroll_range);
         }
@@ -297,18 +467,85 @@ impl LyricState {
         Ok(())
     }
 
+    /// 解析歌词并合并翻译与音译
+    async fn load(&mut self, song: &SongInfo, content: LyricsContent) -> Result<(), LyricsError> {
+        let mut doc = LyricParser::parse(content.lyric, song.duration).await?;
+        if let Some(translation) = content.translation {
+            match LyricParser::parse(translation, song.duration).await {
+                Ok(translation) => doc.merge_translation(&translation),
+                Err(e) => log::warn!("Parse translation failed: {e}"),
+            }
+        }
+        if let Some(romanization) = content.romanization {
+            match LyricParser::parse(romanization, song.duration).await {
+                Ok(romanization) => doc.merge_romanization(&romanization),
+                Err(e) => log::warn!("Parse romanization failed: {e}"),
+            }
+        }
+        self.synced = doc.synced;
+        self.lyrics = doc.lines;
+        Ok(())
+    }
+
+    /// 是否显示翻译行
+    pub fn show_translation(&self) -> bool {
+        get_config().read().unwrap().ui.translation
+            && self.lyrics.iter().any(|line| line.translation.is_some())
+    }
+
+    /// 是否显示音译行
+    pub fn show_romanization(&self) -> bool {
+        get_config().read().unwrap().ui.romanization
+            && self.lyrics.iter().any(|line| line.romanization.is_some())
+    }
+
+    /// 每句歌词占用的行数
+    pub fn rows_per_line(&self) -> usize {
+        1 + self.show_translation() as usize + self.show_romanization() as usize
+    }
+
+    /// 应用偏移后的歌词时间
+    pub fn lyric_time(&self) -> f64 {
+        self.play_time.current_time + self.offset as f64 / 1000.0
+    }
+
     /// 当前播放的 line
     pub fn find_current_line(&self) -> Option<usize> {
+        let time = self.lyric_time();
         self.lyrics
             .iter()
             .enumerate()
-            .find(|(_, line)| {
-                self.play_time.current_time >= line.timestamp_start
-                    && self.play_time.current_time < line.timestamp_end
-            })
+            .find(|(_, line)| time >= line.timestamp_start && time < line.timestamp_end)
             .map(|(i, _)| i)
     }
 
+    /// 手动滚动纯文本歌词
+    pub fn scroll(&mut self, delta: isize) {
+        if self.synced {
+            return;
+        }
+        self.manual_scroll = true;
+        self.target_scroll = self
+            .target_scroll
+            .saturating_add_signed(delta)
+            .min(self.view_metrics.scroll_range);
+    }
+
+    /// 调整歌词偏移并保存
+    pub async fn shift_offset(&mut self, delta: i64) {
+        if self.song.title.is_empty() {
+            return;
+        }
+        self.offset += delta;
+        if let Err(e) = get_lyrics_client()
+            .cache
+            .store_offset(&self.song, self.offset)
+            .await
+        {
+            log::error!("Store offset: {e}");
+        }
+    }
+
     async fn handle_error(&mut self, error: LyricsError) {
         if self.retry_counter < 5 {
             self.retry_counter += 1;
@@ -330,6 +567,32 @@ impl LyricState {
         }
     }
 
+    /// 忽略缓存重新获取歌词，完成后重新加载
+    pub async fn refetch(&mut self) {
+        if self.song.title.is_empty() {
+            return;
+        }
+        if let Err(e) = get_lyrics_client().refetch_lyrics(&self.song).await {
+            log::error!("Refetch: {e}");
+        }
+        self.reset();
+    }
+
+    /// 将当前歌词写入音频文件标签
+    pub async fn embed(&self) {
+        if self.song.title.is_empty() {
+            return;
+        }
+        let client = get_lyrics_client();
+        let result = match client.get_lyrics(&self.song).await {
+            Ok(content) => client.embed(&self.song, &content).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            log::error!("Embed: {e}");
+        }
+    }
+
     pub async fn action(&self, action: PlayerAction) {
         if let Err(e) = player_action(action, &self.song).await {
             log::error!("Action: {e}");
@@ -0,0 +1,356 @@
+mod common;
+
+use std::fs;
+
+use common::{MockServer, Reply, TempDir, new_client, song, sources};
+use lyrics_next::{
+    cache::{CacheManager, CachedSong, GcReport},
+    client::{LyricsContent, LyricsItem},
+    song::SongInfo,
+};
+
+fn item(params: &[(&str, &str)]) -> LyricsItem {
+    LyricsItem {
+        source: "Netease".to_string(),
+        title: "晴天".to_string(),
+        artist: "周杰伦".to_string(),
+        album: "叶惠美".to_string(),
+        duration: Some(269.0),
+        params: params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    }
+}
+
+fn song_titled(title: &str) -> SongInfo {
+    SongInfo {
+        title: title.to_string(),
+        ..song()
+    }
+}
+
+/// 修改索引中的访问时间，模拟很久以前缓存的歌词
+fn set_accessed_at(dir: &TempDir, key: &str, time: u64) {
+    let path = dir.0.join("index.json");
+    let mut index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    index[key]["accessed_at"] = time.into();
+    index[key]["fetched_at"] = time.into();
+    fs::write(&path, serde_json::to_string(&index).unwrap()).unwrap();
+}
+
+async fn store_lyric(cache: &CacheManager, song: &SongInfo) {
+    let content = LyricsContent::new(format!("[00:01.00]{}", "故事的小黄花".repeat(10)));
+    cache.store(song, &item(&[]), None, &content).await.unwrap();
+}
+
+#[tokio::test]
+async fn store_records_provenance() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+    let content = LyricsContent::new("[00:01.00]故事的小黄花".to_string());
+
+    cache
+        .store(
+            &song(),
+            &item(&[("id", "186016"), ("lyric", "[00:01.00]内联")]),
+            Some(0.9),
+            &content,
+        )
+        .await
+        .unwrap();
+
+    let entry = cache.entry(&song()).await.unwrap();
+    assert_eq!(entry.source.as_deref(), Some("Netease"));
+    assert_eq!(entry.params, vec![("id".to_string(), "186016".to_string())]);
+    assert_eq!(entry.score, Some(0.9));
+    assert_eq!(entry.song, CachedSong::from(&song()));
+    assert!(entry.fetched_at > 0);
+    assert_eq!(cache.get(&song()).await.unwrap().lyric, content.lyric);
+
+    // 索引为可读的 JSON
+    let index = fs::read_to_string(dir.0.join("index.json")).unwrap();
+    assert!(index.contains("\"source\": \"Netease\""), "{index}");
+}
+
+#[tokio::test]
+async fn offset_lives_in_index() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+    let content = LyricsContent::new("[00:01.00]故事的小黄花".to_string());
+
+    // 没有缓存歌词时也保存偏移
+    cache.store_offset(&song(), -300).await.unwrap();
+    assert_eq!(cache.get_offset(&song()).await, -300);
+    assert_eq!(cache.entry(&song()).await.unwrap().source, None);
+
+    // 保存歌词时保留偏移
+    cache
+        .store(&song(), &item(&[]), None, &content)
+        .await
+        .unwrap();
+    assert_eq!(cache.get_offset(&song()).await, -300);
+
+    cache.store_offset(&song(), 0).await.unwrap();
+    assert_eq!(cache.get_offset(&song()).await, 0);
+    assert!(cache.entry(&song()).await.is_some());
+
+    cache.delete(&song()).await;
+    assert!(cache.entry(&song()).await.is_none());
+    assert!(cache.get(&song()).await.is_none());
+}
+
+#[tokio::test]
+async fn legacy_offset_file_migrated() {
+    let dir = TempDir::new();
+    let legacy = dir.0.join("周杰伦-晴天-叶惠美.offset");
+    fs::write(&legacy, "500").unwrap();
+    let cache = CacheManager::with_dir(dir.0.clone());
+
+    assert_eq!(cache.get_offset(&song()).await, 500);
+
+    cache
+        .store(
+            &song(),
+            &item(&[]),
+            None,
+            &LyricsContent::new("[00:01.00]故事".to_string()),
+        )
+        .await
+        .unwrap();
+
+    assert!(!legacy.exists());
+    assert_eq!(cache.entry(&song()).await.unwrap().offset, 500);
+}
+
+#[tokio::test]
+async fn client_records_score_and_manual_download() {
+    let server = MockServer::start().await;
+    server
+        .route("/api/search/get/", [Reply::fixture("netease/search.json")])
+        .route("/api/song/lyric/v1", [Reply::fixture("netease/lyric.json")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    client.get_lyrics(&song()).await.unwrap();
+    let entry = client.cache.entry(&song()).await.unwrap();
+    assert_eq!(entry.source.as_deref(), Some("Netease"));
+    assert_eq!(entry.params[0].1, "186016");
+    assert!(entry.score.unwrap() > 0.65);
+
+    let list = client.get_search(&song()).await.unwrap();
+    client.download(&song(), &list[2]).await.unwrap();
+    let entry = client.cache.entry(&song()).await.unwrap();
+    assert_eq!(entry.params, list[2].params);
+    assert_eq!(entry.score, None);
+}
+
+#[tokio::test]
+async fn not_found_entry() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+
+    cache.store_offset(&song(), 200).await.unwrap();
+    cache.store_not_found(&song()).await.unwrap();
+    assert!(cache.is_not_found(&song(), 60).await);
+    assert!(!cache.is_not_found(&song(), 0).await);
+    assert_eq!(cache.get_offset(&song()).await, 200);
+
+    cache.store_offset(&song(), 0).await.unwrap();
+    assert!(cache.is_not_found(&song(), 60).await);
+
+    cache
+        .store(
+            &song(),
+            &item(&[]),
+            None,
+            &LyricsContent::new("[00:01.00]故事".to_string()),
+        )
+        .await
+        .unwrap();
+    assert!(!cache.is_not_found(&song(), 60).await);
+
+    // 已有缓存歌词时不记录
+    cache.store_not_found(&song()).await.unwrap();
+    assert!(!cache.is_not_found(&song(), 60).await);
+}
+
+#[tokio::test]
+async fn gc_removes_expired_entries() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone()).with_limits(0, 30 * 24 * 60 * 60);
+    store_lyric(&cache, &song()).await;
+    store_lyric(&cache, &song_titled("七里香")).await;
+    set_accessed_at(&dir, "周杰伦-晴天-叶惠美", 1_000);
+
+    let report = cache.gc().await.unwrap();
+
+    assert_eq!(report.removed, 1);
+    assert!(report.freed > 0);
+    assert!(cache.get(&song()).await.is_none());
+    assert!(cache.entry(&song()).await.is_none());
+    assert!(cache.get(&song_titled("七里香")).await.is_some());
+}
+
+#[tokio::test]
+async fn gc_evicts_least_recently_used() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+    for (i, title) in ["晴天", "七里香", "稻香"].into_iter().enumerate() {
+        store_lyric(&cache, &song_titled(title)).await;
+        set_accessed_at(&dir, &format!("周杰伦-{title}-叶惠美"), 1_000 + i as u64);
+    }
+    // 读取后变为最近访问
+    assert!(cache.get(&song()).await.is_some());
+
+    let size = fs::metadata(dir.0.join("周杰伦-晴天-叶惠美.lrc"))
+        .unwrap()
+        .len();
+    let cache = cache.with_limits(size * 2, 0);
+    let report = cache.gc().await.unwrap();
+
+    assert_eq!(
+        report,
+        GcReport {
+            removed: 1,
+            freed: size,
+            size: size * 2,
+        }
+    );
+    assert!(cache.get(&song_titled("七里香")).await.is_none());
+    assert!(cache.get(&song()).await.is_some());
+    assert!(cache.get(&song_titled("稻香")).await.is_some());
+}
+
+#[tokio::test]
+async fn gc_skips_offset_only_entries_when_oversize() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+    // 只有偏移的记录最旧，但不占空间，不应被超出大小的清理删除
+    cache
+        .store_offset(&song_titled("七里香"), 300)
+        .await
+        .unwrap();
+    set_accessed_at(&dir, "周杰伦-七里香-叶惠美", 1_000);
+    store_lyric(&cache, &song()).await;
+    set_accessed_at(&dir, "周杰伦-晴天-叶惠美", 1_001);
+    store_lyric(&cache, &song_titled("稻香")).await;
+
+    let size = fs::metadata(dir.0.join("周杰伦-晴天-叶惠美.lrc"))
+        .unwrap()
+        .len();
+    let report = cache.clone().with_limits(size, 0).gc().await.unwrap();
+
+    assert_eq!(
+        report,
+        GcReport {
+            removed: 1,
+            freed: size,
+            size,
+        }
+    );
+    assert!(cache.get(&song()).await.is_none());
+    assert_eq!(cache.get_offset(&song_titled("七里香")).await, 300);
+}
+
+#[tokio::test]
+async fn gc_ages_out_offset_only_entries() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone()).with_limits(0, 60);
+    cache.store_offset(&song(), 300).await.unwrap();
+    set_accessed_at(&dir, "周杰伦-晴天-叶惠美", 1_000);
+
+    let report = cache.gc().await.unwrap();
+
+    assert_eq!(report, GcReport::default());
+    assert_eq!(cache.get_offset(&song()).await, 0);
+}
+
+#[tokio::test]
+async fn offset_lookup_counts_as_access() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone()).with_limits(0, 60);
+    cache.store_offset(&song(), 300).await.unwrap();
+    set_accessed_at(&dir, "周杰伦-晴天-叶惠美", 1_000);
+
+    // 播放时读取偏移，记录不会因为没有歌词文件而过期
+    assert_eq!(cache.get_offset(&song()).await, 300);
+    cache.gc().await.unwrap();
+    assert_eq!(cache.get_offset(&song()).await, 300);
+}
+
+#[tokio::test]
+async fn gc_without_limits_keeps_everything() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+    store_lyric(&cache, &song()).await;
+    set_accessed_at(&dir, "周杰伦-晴天-叶惠美", 1_000);
+
+    let report = cache.gc().await.unwrap();
+
+    assert_eq!(report.removed, 0);
+    assert!(cache.get(&song()).await.is_some());
+}
+
+#[tokio::test]
+async fn access_time_is_written_on_gc() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone());
+    store_lyric(&cache, &song()).await;
+    set_accessed_at(&dir, "周杰伦-晴天-叶惠美", 1_000);
+    let on_disk = || {
+        let index: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.0.join("index.json")).unwrap()).unwrap();
+        index["周杰伦-晴天-叶惠美"]["accessed_at"].as_u64().unwrap()
+    };
+
+    // 读取时不写索引，但查询索引能看到新的访问时间
+    assert!(cache.get(&song()).await.is_some());
+    assert_eq!(on_disk(), 1_000);
+    assert!(cache.entry(&song()).await.unwrap().accessed_at > 1_000);
+
+    cache.gc().await.unwrap();
+    assert!(on_disk() > 1_000);
+}
+
+#[tokio::test]
+async fn gc_keeps_index_and_files_consistent_with_stores() {
+    let dir = TempDir::new();
+    let cache = CacheManager::with_dir(dir.0.clone()).with_limits(1, 0);
+    let songs: Vec<SongInfo> = (0..20).map(|i| song_titled(&format!("歌{i}"))).collect();
+
+    let stores = futures::future::join_all(songs.iter().map(|song| store_lyric(&cache, song)));
+    let (_, report) = tokio::join!(stores, cache.gc());
+    report.unwrap();
+
+    // 清理与写入交错时，索引中的歌词文件都存在，没有索引的歌词文件都已删除
+    let index = cache.index().await;
+    for song in &songs {
+        let key = format!("周杰伦-{}-叶惠美", song.title);
+        let file = dir.0.join(format!("{key}.lrc"));
+        assert_eq!(index.contains_key(&key), file.exists(), "{key}");
+    }
+}
+
+#[tokio::test]
+async fn legacy_cache_moved_to_cache_dir() {
+    let dir = TempDir::new();
+    let legacy = dir.0.clone();
+    fs::write(legacy.join("周杰伦-晴天-叶惠美.lrc"), "[00:01.00]故事").unwrap();
+    fs::write(
+        legacy.join("周杰伦-晴天-叶惠美.trans.lrc"),
+        "[00:01.00]Story",
+    )
+    .unwrap();
+    fs::write(legacy.join("lyrics.toml"), "").unwrap();
+    let cache = CacheManager::with_dir(legacy.join("cache"));
+    fs::create_dir_all(legacy.join("cache")).unwrap();
+
+    assert_eq!(cache.migrate_legacy(&legacy), 2);
+
+    assert!(legacy.join("lyrics.toml").exists());
+    assert!(!legacy.join("周杰伦-晴天-叶惠美.lrc").exists());
+    let content = cache.get(&song()).await.unwrap();
+    assert_eq!(content.lyric, "[00:01.00]故事");
+    assert_eq!(content.translation.as_deref(), Some("[00:01.00]Story"));
+}
@@ -0,0 +1,238 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{MockServer, Reply, new_client, song, sources};
+use lyrics_next::{config::Sources, error::LyricsError};
+
+const NETEASE_SEARCH: &str = "/api/search/get/";
+const NETEASE_LYRIC: &str = "/api/song/lyric/v1";
+
+async fn all_sources(netease_lyric: Reply) -> MockServer {
+    let server = MockServer::start().await;
+    server
+        .route(NETEASE_SEARCH, [Reply::fixture("netease/search.json")])
+        .route(NETEASE_LYRIC, [netease_lyric])
+        .route(
+            "/soso/fcgi-bin/client_search_cp",
+            [Reply::fixture("qqmusic/search.json")],
+        )
+        .route(
+            "/lyric/fcgi-bin/fcg_query_lyric_new.fcg",
+            [Reply::fixture("qqmusic/lyric.json")],
+        )
+        .route("/api/v3/search/song", [Reply::fixture("kugou/search.json")])
+        .route("/search", [Reply::fixture("kugou/candidates.json")])
+        .route("/download", [Reply::fixture("kugou/download_krc.json")]);
+    server
+}
+
+fn ordered(server: &MockServer, order: &[&str]) -> Sources {
+    let list: Vec<_> = order
+        .iter()
+        .map(|name| (*name, server.settings()))
+        .collect();
+    sources(&list)
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let server = MockServer::start().await;
+    server.route(
+        NETEASE_SEARCH,
+        [
+            Reply::Body(502, "bad gateway".to_string()),
+            Reply::fixture("netease/search.json"),
+        ],
+    );
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let list = client.get_search(&song()).await.unwrap();
+
+    assert_eq!(list.len(), 3);
+    assert_eq!(server.hits(NETEASE_SEARCH), 2);
+}
+
+#[tokio::test]
+async fn retries_dropped_connections() {
+    let server = MockServer::start().await;
+    server.route(
+        NETEASE_SEARCH,
+        [Reply::Drop, Reply::fixture("netease/search.json")],
+    );
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    assert!(client.get_search(&song()).await.is_ok());
+    assert_eq!(server.hits(NETEASE_SEARCH), 2);
+}
+
+#[tokio::test]
+async fn gives_up_after_retries() {
+    let server = MockServer::start().await;
+    server.route(NETEASE_SEARCH, [Reply::Drop]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let err = client.get_search(&song()).await.unwrap_err();
+
+    assert!(matches!(err, LyricsError::ReqwestError(_)), "{err}");
+    assert_eq!(server.hits(NETEASE_SEARCH), 2);
+}
+
+#[tokio::test]
+async fn server_error_after_retries_fails() {
+    let server = MockServer::start().await;
+    server.route(
+        NETEASE_SEARCH,
+        [Reply::Body(503, "<html>unavailable</html>".to_string())],
+    );
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let err = client.get_search(&song()).await.unwrap_err();
+
+    let LyricsError::ReqwestError(e) = err else {
+        panic!("{err}");
+    };
+    assert_eq!(e.status().map(|s| s.as_u16()), Some(503));
+    assert_eq!(server.hits(NETEASE_SEARCH), 2);
+}
+
+#[tokio::test]
+async fn malformed_json_fails() {
+    let server = MockServer::start().await;
+    server.route(NETEASE_SEARCH, [Reply::ok("<html>busy</html>")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    assert!(client.get_search(&song()).await.is_err());
+    assert!(matches!(
+        client.get_lyrics(&song()).await,
+        Err(LyricsError::NoLyricsFound)
+    ));
+}
+
+#[tokio::test]
+async fn unrelated_results_are_rejected() {
+    let server = all_sources(Reply::fixture("netease/lyric.json")).await;
+    let (client, _dir) = new_client(&ordered(&server, &["netease", "qq", "kugou"]));
+    let other = lyrics_next::song::SongInfo {
+        title: "完全不同的歌".to_string(),
+        artist: "陌生人".to_string(),
+        ..song()
+    };
+
+    assert!(matches!(
+        client.get_lyrics(&other).await,
+        Err(LyricsError::NoLyricsFound)
+    ));
+    assert_eq!(server.hits(NETEASE_LYRIC), 0);
+}
+
+#[tokio::test]
+async fn prefers_word_timed_then_source_order() {
+    let server = all_sources(Reply::fixture("netease/lyric.json")).await;
+
+    let (client, _dir) = new_client(&ordered(&server, &["qq", "netease", "kugou"]));
+    let content = client.get_lyrics(&song()).await.unwrap();
+    assert!(
+        content.lyric.contains("<00:01.00>故事"),
+        "{}",
+        content.lyric
+    );
+
+    let (client, _dir) = new_client(&ordered(&server, &["kugou", "netease", "qq"]));
+    let content = client.get_lyrics(&song()).await.unwrap();
+    assert!(
+        content.lyric.contains("<00:01.00>从出生"),
+        "{}",
+        content.lyric
+    );
+}
+
+#[tokio::test]
+async fn slow_sources_hit_the_deadline() {
+    let server = all_sources(Reply::Delay(
+        Duration::from_secs(5),
+        Box::new(Reply::fixture("netease/lyric.json")),
+    ))
+    .await;
+    let mut config = ordered(&server, &["netease", "qq"]);
+    config.deadline = 1;
+    let (client, _dir) = new_client(&config);
+
+    let start = Instant::now();
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(3));
+    assert!(content.lyric.contains("[00:01.00]故事的小黄花"));
+}
+
+#[tokio::test]
+async fn no_lyrics_is_cached_until_refetch() {
+    let server = MockServer::start().await;
+    server
+        .route(
+            NETEASE_SEARCH,
+            [
+                Reply::fixture("netease/search_empty.json"),
+                Reply::fixture("netease/search_empty.json"),
+                Reply::fixture("netease/search.json"),
+            ],
+        )
+        .route(NETEASE_LYRIC, [Reply::fixture("netease/lyric.json")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    assert!(matches!(
+        client.get_lyrics(&song()).await,
+        Err(LyricsError::NoLyricsFound)
+    ));
+    assert!(matches!(
+        client.get_lyrics(&song()).await,
+        Err(LyricsError::NoLyricsCached)
+    ));
+    assert_eq!(server.hits(NETEASE_SEARCH), 1);
+
+    assert!(client.refetch_lyrics(&song()).await.is_err());
+    assert!(client.cache.entry(&song()).await.unwrap().not_found);
+
+    let content = client.refetch_lyrics(&song()).await.unwrap();
+    assert_eq!(
+        client.get_lyrics(&song()).await.unwrap().lyric,
+        content.lyric
+    );
+    assert!(!client.cache.entry(&song()).await.unwrap().not_found);
+    assert_eq!(server.hits(NETEASE_SEARCH), 3);
+}
+
+#[tokio::test]
+async fn network_failures_are_not_cached() {
+    let server = MockServer::start().await;
+    server.route(NETEASE_SEARCH, [Reply::Drop]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    assert!(client.get_lyrics(&song()).await.is_err());
+    assert!(client.get_lyrics(&song()).await.is_err());
+
+    assert!(client.cache.entry(&song()).await.is_none());
+    assert_eq!(server.hits(NETEASE_SEARCH), 4);
+}
+
+#[tokio::test]
+async fn no_lyrics_cache_can_be_disabled() {
+    let server = MockServer::start().await;
+    server.route(
+        NETEASE_SEARCH,
+        [Reply::fixture("netease/search_empty.json")],
+    );
+    let sources = Sources {
+        not_found_ttl: 0,
+        ..sources(&[("netease", server.settings())])
+    };
+    let (client, _dir) = new_client(&sources);
+
+    for _ in 0..2 {
+        assert!(matches!(
+            client.get_lyrics(&song()).await,
+            Err(LyricsError::NoLyricsFound)
+        ));
+    }
+    assert_eq!(server.hits(NETEASE_SEARCH), 2);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{new_client, song, sources};
+use lyrics_next::{client::LyricsClient, config::SourceSettings, error::LyricsError};
+
+fn provider(mode: &str) -> (LyricsClient, common::TempDir) {
+    let script = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/command/provider.sh"
+    );
+    let settings = SourceSettings {
+        timeout: 1,
+        command: Some("sh".to_string()),
+        args: vec![script.to_string(), mode.to_string()],
+        ..Default::default()
+    };
+    new_client(&sources(&[("command", settings)]))
+}
+
+#[tokio::test]
+async fn lrc_output_is_used_directly() {
+    let (client, _dir) = provider("lrc");
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(content.lyric, "[ti:晴天]\n[00:01.00]search by 周杰伦\n");
+}
+
+#[tokio::test]
+async fn song_is_passed_on_stdin() {
+    let (client, _dir) = provider("stdin");
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(content.lyric.trim_start_matches("[00:01.00]")).unwrap();
+    assert_eq!(json["action"], "search");
+    assert_eq!(json["title"], "晴天");
+    assert_eq!(json["album"], "叶惠美");
+    assert_eq!(json["duration"], 269.5);
+}
+
+#[tokio::test]
+async fn candidates_are_matched_then_downloaded() {
+    let (client, _dir) = provider("list");
+
+    let list = client.get_search(&song()).await.unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].source, "Command");
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+    assert_eq!(content.lyric, "[00:01.00]downloaded studio");
+    assert_eq!(content.translation.as_deref(), Some("[00:01.00]translated"));
+}
+
+#[tokio::test]
+async fn slow_commands_time_out() {
+    let (client, _dir) = provider("slow");
+
+    let start = Instant::now();
+    assert!(client.get_lyrics(&song()).await.is_err());
+    assert!(start.elapsed() < Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn failed_commands_are_not_cached() {
+    let (client, _dir) = provider("fail");
+
+    let err = client.get_search(&song()).await.unwrap_err();
+    let LyricsError::CommandFailed { status, stderr } = err else {
+        panic!("{err}");
+    };
+    assert_eq!(status.code(), Some(1));
+    assert_eq!(stderr, "database offline");
+
+    assert!(client.get_lyrics(&song()).await.is_err());
+    assert!(client.cache.entry(&song()).await.is_none());
+}
+
+#[tokio::test]
+async fn empty_output_is_no_lyrics() {
+    let (client, _dir) = provider("empty");
+
+    assert!(matches!(
+        client.get_lyrics(&song()).await,
+        Err(LyricsError::NoLyricsFound)
+    ));
+    assert!(client.cache.entry(&song()).await.unwrap().not_found);
+}
+
+#[tokio::test]
+async fn error_pages_are_not_lyrics() {
+    let (client, _dir) = provider("html");
+
+    assert!(client.get_lyrics(&song()).await.is_err());
+    assert!(client.cache.get(&song()).await.is_none());
+    assert!(client.cache.entry(&song()).await.is_none());
+}
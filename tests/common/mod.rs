@@ -0,0 +1,268 @@
+#![allow(dead_code)]
+
+//! 本地 HTTP 替身，按路径返回录制的响应，并记录收到的请求
+
+pub mod audio;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use lyrics_next::{
+    cache::CacheManager,
+    client::LyricsClient,
+    config::{SourceSettings, Sources},
+    song::SongInfo,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// 单次响应
+#[derive(Debug, Clone)]
+pub enum Reply {
+    /// 状态码与响应体
+    Body(u16, String),
+    /// 延迟后再响应
+    Delay(Duration, Box<Reply>),
+    /// 不响应直接断开连接
+    Drop,
+}
+
+impl Reply {
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::Body(200, body.into())
+    }
+
+    pub fn fixture(name: &str) -> Self {
+        Self::ok(fixture(name))
+    }
+}
+
+type Routes = HashMap<String, VecDeque<Reply>>;
+/// 收到的请求 (请求目标, 请求头)
+type Requests = Vec<(String, Vec<(String, String)>)>;
+
+pub struct MockServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<Routes>>,
+    requests: Arc<Mutex<Requests>>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes = Arc::new(Mutex::new(Routes::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let (r, q) = (routes.clone(), requests.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, r.clone(), q.clone()));
+            }
+        });
+
+        Self {
+            addr,
+            routes,
+            requests,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 为路径添加响应，依次返回，最后一个重复使用
+    pub fn route(&self, path: &str, replies: impl IntoIterator<Item = Reply>) -> &Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .extend(replies);
+        self
+    }
+
+    /// 收到的请求目标，包含查询参数
+    pub fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(target, _)| target.clone())
+            .collect()
+    }
+
+    /// 指定路径最后一次请求中的请求头，名称不区分大小写
+    pub fn header(&self, path: &str, name: &str) -> Option<String> {
+        let requests = self.requests.lock().unwrap();
+        let (_, headers) = requests
+            .iter()
+            .rfind(|(target, _)| target.split('?').next() == Some(path))?;
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+
+    /// 指定路径收到的请求数
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|target| target.split('?').next() == Some(path))
+            .count()
+    }
+
+    /// 指向本服务的来源配置
+    pub fn settings(&self) -> SourceSettings {
+        SourceSettings {
+            timeout: 2,
+            retries: 1,
+            base_url: Some(self.url()),
+            ..Default::default()
+        }
+    }
+}
+
+async fn handle(mut stream: TcpStream, routes: Arc<Mutex<Routes>>, requests: Arc<Mutex<Requests>>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let target = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let path = target.split('?').next().unwrap_or("/").to_string();
+    let headers = head
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+    requests.lock().unwrap().push((target, headers));
+
+    let reply = {
+        let mut routes = routes.lock().unwrap();
+        match routes.get_mut(&path) {
+            Some(queue) if queue.len() > 1 => queue.pop_front(),
+            Some(queue) => queue.front().cloned(),
+            None => None,
+        }
+    };
+
+    let mut reply = reply.unwrap_or(Reply::Body(404, "not found".to_string()));
+    loop {
+        match reply {
+            Reply::Delay(delay, next) => {
+                tokio::time::sleep(delay).await;
+                reply = *next;
+            }
+            Reply::Drop => return,
+            Reply::Body(status, body) => {
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+                return;
+            }
+        }
+    }
+}
+
+/// 读取录制的响应
+pub fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+/// 只启用指定来源的配置
+pub fn sources(enabled: &[(&str, SourceSettings)]) -> Sources {
+    let disabled = SourceSettings {
+        enabled: false,
+        ..Default::default()
+    };
+    let mut sources = Sources {
+        order: enabled.iter().map(|(name, _)| name.to_string()).collect(),
+        local: disabled.clone(),
+        embedded: disabled.clone(),
+        netease: disabled.clone(),
+        qq: disabled.clone(),
+        kugou: disabled.clone(),
+        lrclib: disabled.clone(),
+        ovh: disabled.clone(),
+        command: disabled,
+        ..Default::default()
+    };
+    for (name, settings) in enabled {
+        match *name {
+            "local" => sources.local = settings.clone(),
+            "embedded" => sources.embedded = settings.clone(),
+            "netease" => sources.netease = settings.clone(),
+            "qq" => sources.qq = settings.clone(),
+            "kugou" => sources.kugou = settings.clone(),
+            "lrclib" => sources.lrclib = settings.clone(),
+            "ovh" => sources.ovh = settings.clone(),
+            "command" => sources.command = settings.clone(),
+            _ => panic!("unknown source {name}"),
+        }
+    }
+    sources
+}
+
+/// 使用临时缓存目录的客户端
+pub fn new_client(sources: &Sources) -> (LyricsClient, TempDir) {
+    let dir = TempDir::new();
+    let client = LyricsClient::with_config(sources, CacheManager::with_dir(dir.0.clone()));
+    (client, dir)
+}
+
+/// 测试结束时删除的临时目录
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "lyrics-next-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn song() -> SongInfo {
+    SongInfo {
+        title: "晴天".to_string(),
+        artist: "周杰伦".to_string(),
+        album: "叶惠美".to_string(),
+        duration: 269.5,
+        ..Default::default()
+    }
+}
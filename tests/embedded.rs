@@ -0,0 +1,353 @@
+mod common;
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use common::{MockServer, Reply, TempDir, audio, new_client, song, sources};
+use lofty::{
+    TextEncoding,
+    config::{ParseOptions, WriteOptions},
+    id3::v2::{
+        BinaryFrame, ExtendedTextFrame, Frame, FrameId, Id3v2Tag, SyncTextContentType,
+        SynchronizedTextFrame, TimestampFormat, UnsynchronizedTextFrame,
+    },
+    mp4::{Atom, AtomData, AtomIdent, Ilst},
+    mpeg::MpegFile,
+    ogg::tag::VorbisComments,
+    prelude::*,
+};
+use lyrics_next::{
+    client::{LyricsClient, LyricsContent},
+    config::SourceSettings,
+    song::{LyricParser, SongInfo},
+};
+
+const LYRIC: &str = "[00:01.00]故事的小黄花";
+
+fn playing(path: &Path) -> SongInfo {
+    SongInfo {
+        url: reqwest::Url::from_file_path(path).unwrap().to_string(),
+        ..song()
+    }
+}
+
+fn embedded() -> (LyricsClient, TempDir) {
+    new_client(&sources(&[("embedded", SourceSettings::default())]))
+}
+
+fn sylt(content: &[(u32, &str)]) -> Frame<'static> {
+    let frame = SynchronizedTextFrame::new(
+        TextEncoding::UTF8,
+        *b"chi",
+        TimestampFormat::MS,
+        SyncTextContentType::Lyrics,
+        None,
+        content.iter().map(|(t, s)| (*t, s.to_string())).collect(),
+    );
+    let data = frame.as_bytes(WriteOptions::default()).unwrap();
+    Frame::Binary(BinaryFrame::new(FrameId::new("SYLT").unwrap(), data))
+}
+
+fn uslt(text: &str) -> Frame<'static> {
+    Frame::UnsynchronizedText(UnsynchronizedTextFrame::new(
+        TextEncoding::UTF8,
+        *b"chi",
+        String::new(),
+        text.to_string(),
+    ))
+}
+
+fn txxx(description: &str, text: &str) -> Frame<'static> {
+    Frame::UserText(ExtendedTextFrame::new(
+        TextEncoding::UTF8,
+        description.to_string(),
+        text.to_string(),
+    ))
+}
+
+fn mp3(dir: &TempDir, frames: Vec<Frame<'static>>) -> PathBuf {
+    let path = dir.0.join("晴天.mp3");
+    audio::mp3(&path);
+    let mut tag = Id3v2Tag::new();
+    for frame in frames {
+        tag.insert(frame);
+    }
+    tag.save_to_path(&path, WriteOptions::default()).unwrap();
+    path
+}
+
+/// ID3v2 帧的 ID 与 USLT 内容
+fn frames(path: &Path) -> Vec<(String, String)> {
+    let mpeg = MpegFile::read_from(&mut File::open(path).unwrap(), ParseOptions::new()).unwrap();
+    mpeg.id3v2()
+        .into_iter()
+        .flatten()
+        .map(|frame| {
+            let text = match frame {
+                Frame::UnsynchronizedText(uslt) => uslt.content.to_string(),
+                _ => String::new(),
+            };
+            (frame.id().as_str().to_string(), text)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn sylt_words_become_enhanced_lrc() {
+    let music = TempDir::new();
+    let path = mp3(
+        &music,
+        vec![
+            sylt(&[(1000, "故事"), (1500, "的"), (2000, "\n小黄花"), (3000, "")]),
+            uslt("[00:01.00]USLT"),
+        ],
+    );
+    let (client, _dir) = embedded();
+
+    let content = client.get_lyrics(&playing(&path)).await.unwrap();
+
+    assert_eq!(
+        content.lyric,
+        "[00:01.00]<00:01.00>故事<00:01.50>的<00:02.00>\n[00:02.00]<00:02.00>小黄花<00:03.00>"
+    );
+}
+
+#[tokio::test]
+async fn last_sylt_word_is_left_open() {
+    let music = TempDir::new();
+    let path = mp3(
+        &music,
+        vec![sylt(&[(1000, "故事"), (1500, "的"), (2000, "\n小黄花")])],
+    );
+    let (client, _dir) = embedded();
+
+    let content = client.get_lyrics(&playing(&path)).await.unwrap();
+    assert_eq!(
+        content.lyric,
+        "[00:01.00]<00:01.00>故事<00:01.50>的<00:02.00>\n[00:02.00]<00:02.00>小黄花"
+    );
+
+    // 按播放器的时长补全最后一个字
+    let doc = LyricParser::parse(content.lyric, song().duration)
+        .await
+        .unwrap();
+    let last = &doc.lines[1].words.as_ref().unwrap()[0];
+    assert_eq!(last.timestamp_end, song().duration);
+}
+
+#[tokio::test]
+async fn sylt_lines_become_lrc() {
+    let music = TempDir::new();
+    let path = mp3(
+        &music,
+        vec![sylt(&[(1000, "故事的小黄花"), (4500, "从出生那年就飘着")])],
+    );
+    let (client, _dir) = embedded();
+
+    let content = client.get_lyrics(&playing(&path)).await.unwrap();
+
+    assert_eq!(
+        content.lyric,
+        "[00:01.00]故事的小黄花\n[00:04.50]从出生那年就飘着"
+    );
+}
+
+#[tokio::test]
+async fn uslt_without_sylt() {
+    let music = TempDir::new();
+    let path = mp3(&music, vec![uslt("[00:01.00]故事的小黄花")]);
+    let (client, _dir) = embedded();
+
+    let content = client.get_lyrics(&playing(&path)).await.unwrap();
+
+    assert_eq!(content.lyric, "[00:01.00]故事的小黄花");
+    assert!(client.cache.get(&playing(&path)).await.is_none());
+}
+
+#[tokio::test]
+async fn flac_vorbis_comments() {
+    for key in ["LYRICS", "UNSYNCEDLYRICS"] {
+        let music = TempDir::new();
+        let path = music.0.join("晴天.flac");
+        audio::flac(&path);
+        let mut tag = VorbisComments::default();
+        tag.insert(key.to_string(), format!("[00:01.00]{key}"));
+        tag.save_to_path(&path, WriteOptions::default()).unwrap();
+        let (client, _dir) = embedded();
+
+        let content = client.get_lyrics(&playing(&path)).await.unwrap();
+
+        assert_eq!(content.lyric, format!("[00:01.00]{key}"));
+    }
+}
+
+#[tokio::test]
+async fn mp4_lyr_atom() {
+    let music = TempDir::new();
+    let path = music.0.join("晴天.m4a");
+    audio::m4a(&path);
+    let mut tag = Ilst::default();
+    tag.insert(Atom::new(
+        AtomIdent::Fourcc(*b"\xa9lyr"),
+        AtomData::UTF8("故事的小黄花".to_string()),
+    ));
+    tag.save_to_path(&path, WriteOptions::default()).unwrap();
+    let (client, _dir) = embedded();
+
+    let content = client.get_lyrics(&playing(&path)).await.unwrap();
+
+    assert_eq!(content.lyric, "故事的小黄花");
+}
+
+#[tokio::test]
+async fn file_without_lyrics() {
+    let music = TempDir::new();
+    let path = mp3(&music, vec![]);
+    let (client, _dir) = embedded();
+
+    assert!(client.get_lyrics(&playing(&path)).await.is_err());
+    assert!(client.get_search(&playing(&path)).await.is_err());
+}
+
+fn write_back(server: &MockServer) -> (LyricsClient, TempDir) {
+    let embedded = SourceSettings {
+        write: true,
+        ..Default::default()
+    };
+    new_client(&sources(&[
+        ("embedded", embedded),
+        ("netease", server.settings()),
+    ]))
+}
+
+async fn netease() -> MockServer {
+    let server = MockServer::start().await;
+    server
+        .route("/api/search/get/", [Reply::fixture("netease/search.json")])
+        .route("/api/song/lyric/v1", [Reply::fixture("netease/lyric.json")]);
+    server
+}
+
+#[tokio::test]
+async fn fetched_lyric_written_to_mp3() {
+    let music = TempDir::new();
+    let song = playing(&mp3(&music, vec![]));
+    let server = netease().await;
+    let (client, _dir) = write_back(&server);
+
+    let fetched = client.get_lyrics(&song).await.unwrap();
+
+    assert_eq!(
+        frames(&music.0.join("晴天.mp3")),
+        vec![
+            (
+                "USLT".to_string(),
+                "故事的小黄花\n从出生那年就飘着".to_string()
+            ),
+            ("SYLT".to_string(), String::new())
+        ]
+    );
+    assert!(fetched.lyric.contains("<00:01.00>故事"));
+
+    // 写回后由内嵌来源读取，不再请求网络
+    let requests = server.requests().len();
+    let (client, _dir) = write_back(&server);
+    let content = client.get_lyrics(&song).await.unwrap();
+    assert!(content.lyric.contains("<00:01.00>故事"));
+    assert_eq!(server.requests().len(), requests);
+}
+
+#[tokio::test]
+async fn embed_replaces_mp3_lyrics() {
+    let music = TempDir::new();
+    let path = mp3(
+        &music,
+        vec![
+            uslt("旧歌词"),
+            sylt(&[(0, "旧歌词")]),
+            txxx("UNSYNCEDLYRICS", "旧歌词"),
+            txxx("lyrics", "旧歌词"),
+            txxx("SOURCE", "CD"),
+        ],
+    );
+    let (client, _dir) = embedded();
+
+    client
+        .embed(&playing(&path), &LyricsContent::new(LYRIC.to_string()))
+        .await
+        .unwrap();
+
+    // USLT 不带时间，时间只保存在 SYLT 中
+    assert_eq!(
+        frames(&path),
+        vec![
+            ("TXXX".to_string(), String::new()),
+            ("USLT".to_string(), "故事的小黄花".to_string()),
+            ("SYLT".to_string(), String::new())
+        ]
+    );
+    let content = client.get_lyrics(&playing(&path)).await.unwrap();
+    assert_eq!(content.lyric, LYRIC);
+}
+
+#[tokio::test]
+async fn embed_flac_lyrics_comment() {
+    let music = TempDir::new();
+    let path = music.0.join("晴天.flac");
+    audio::flac(&path);
+    let mut tag = VorbisComments::default();
+    tag.insert("TITLE".to_string(), "晴天".to_string());
+    tag.insert("UNSYNCEDLYRICS".to_string(), "旧歌词".to_string());
+    tag.push("lyrics".to_string(), "旧歌词".repeat(50));
+    tag.save_to_path(&path, WriteOptions::default()).unwrap();
+    let (client, _dir) = embedded();
+
+    client
+        .embed(&playing(&path), &LyricsContent::new(LYRIC.to_string()))
+        .await
+        .unwrap();
+
+    let tagged = lofty::read_from_path(&path).unwrap();
+    let tag = tagged.primary_tag().unwrap();
+    assert_eq!(tag.get_string(ItemKey::Lyrics), Some(LYRIC));
+    assert_eq!(tag.get_string(ItemKey::TrackTitle), Some("晴天"));
+    assert_eq!(tag.get_strings(ItemKey::Lyrics).count(), 1);
+    assert_eq!(tag.get_string(ItemKey::UnsyncLyrics), None);
+}
+
+#[tokio::test]
+async fn write_back_disabled_by_default() {
+    let music = TempDir::new();
+    let song = playing(&mp3(&music, vec![]));
+    let server = netease().await;
+    let (client, _dir) = new_client(&sources(&[
+        ("embedded", SourceSettings::default()),
+        ("netease", server.settings()),
+    ]));
+
+    client.get_lyrics(&song).await.unwrap();
+
+    let (client, _dir) = embedded();
+    assert!(client.get_lyrics(&song).await.is_err());
+}
+
+#[tokio::test]
+async fn embed_rejects_read_only_and_remote_files() {
+    let music = TempDir::new();
+    let path = mp3(&music, vec![]);
+    let mut permissions = fs::metadata(&path).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&path, permissions).unwrap();
+    let (client, _dir) = embedded();
+    let content = LyricsContent::new(LYRIC.to_string());
+
+    assert!(client.embed(&playing(&path), &content).await.is_err());
+
+    let stream = SongInfo {
+        url: "https://example.com/stream.mp3".to_string(),
+        ..song()
+    };
+    assert!(client.embed(&stream, &content).await.is_err());
+}
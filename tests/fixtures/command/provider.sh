@@ -0,0 +1,36 @@
+#!/bin/sh
+# 测试用歌词命令，第一个参数选择行为
+input=$(cat)
+
+case "$1" in
+lrc)
+    printf '[ti:%s]\n[00:01.00]%s by %s\n' "$LYRICS_TITLE" "$LYRICS_ACTION" "$LYRICS_ARTIST"
+    ;;
+stdin)
+    printf '[00:01.00]%s\n' "$input"
+    ;;
+list)
+    if [ "$LYRICS_ACTION" = search ]; then
+        cat <<JSON
+[
+  {"id": "live", "title": "$LYRICS_TITLE (Live)", "artist": "$LYRICS_ARTIST", "duration": 400},
+  {"id": "studio", "title": "$LYRICS_TITLE", "artist": "$LYRICS_ARTIST", "album": "$LYRICS_ALBUM", "duration": $LYRICS_DURATION}
+]
+JSON
+    else
+        printf '{"lyric": "[00:01.00]downloaded %s", "translation": "[00:01.00]translated"}\n' "$LYRICS_ID"
+    fi
+    ;;
+html)
+    printf '<!DOCTYPE html>\n<html><body>\n<h1>502 Bad Gateway</h1>\n</body></html>\n'
+    ;;
+empty)
+    ;;
+slow)
+    sleep 5
+    ;;
+fail)
+    echo "database offline" >&2
+    exit 1
+    ;;
+esac
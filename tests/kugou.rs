@@ -0,0 +1,83 @@
+mod common;
+
+use common::{MockServer, Reply, new_client, song, sources};
+
+const SEARCH: &str = "/api/v3/search/song";
+const CANDIDATES: &str = "/search";
+const DOWNLOAD: &str = "/download";
+
+fn server_with(download: &str) -> impl Future<Output = MockServer> {
+    let download = download.to_string();
+    async move {
+        let server = MockServer::start().await;
+        server
+            .route(SEARCH, [Reply::fixture("kugou/search.json")])
+            .route(CANDIDATES, [Reply::fixture("kugou/candidates.json")])
+            .route(DOWNLOAD, [Reply::fixture(&download)]);
+        server
+    }
+}
+
+#[tokio::test]
+async fn search_resolves_candidates() {
+    let server = server_with("kugou/download_krc.json").await;
+    let (client, _dir) = new_client(&sources(&[("kugou", server.settings())]));
+
+    let list = client.get_search(&song()).await.unwrap();
+
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].source, "Kugou");
+    assert_eq!(list[0].album, "叶惠美");
+    assert_eq!(list[0].duration, Some(269.0));
+    let candidates = server
+        .requests()
+        .into_iter()
+        .find(|r| r.starts_with(CANDIDATES))
+        .unwrap();
+    assert!(candidates.contains("album_id=960399"), "{candidates}");
+}
+
+#[tokio::test]
+async fn krc_is_decrypted_with_translation() {
+    let server = server_with("kugou/download_krc.json").await;
+    let (client, _dir) = new_client(&sources(&[("kugou", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    let download = server
+        .requests()
+        .into_iter()
+        .find(|r| r.starts_with(DOWNLOAD))
+        .unwrap();
+    assert!(download.contains("accesskey=8A1E3F0BA1D5A2C4C0C1F2E3D4B5A697"));
+    assert!(download.contains("fmt=krc"));
+    assert!(
+        content
+            .lyric
+            .contains("[00:01.00]<00:01.00>从出生<00:02.00>那年就飘着")
+    );
+    assert!(
+        content
+            .translation
+            .unwrap()
+            .contains("[00:04.00]Swinging in childhood")
+    );
+}
+
+#[tokio::test]
+async fn plain_lrc_is_decoded() {
+    let server = server_with("kugou/download_lrc.json").await;
+    let (client, _dir) = new_client(&sources(&[("kugou", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert!(content.lyric.starts_with("[00:01.00]从出生那年就飘着"));
+}
+
+#[tokio::test]
+async fn corrupt_krc_fails() {
+    let server = server_with("kugou/download_corrupt.json").await;
+    let (client, _dir) = new_client(&sources(&[("kugou", server.settings())]));
+
+    assert!(client.get_lyrics(&song()).await.is_err());
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use std::fs;
+
+use common::{MockServer, Reply, TempDir, new_client, song, sources};
+use lyrics_next::{config::SourceSettings, song::SongInfo};
+
+const LYRIC: &str = "[00:01.00]本地歌词\n";
+
+fn local(dirs: &[&TempDir], patterns: &[&str]) -> SourceSettings {
+    SourceSettings {
+        dirs: dirs
+            .iter()
+            .map(|d| d.0.to_string_lossy().into_owned())
+            .collect(),
+        patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn playing(music: &TempDir, file: &str) -> SongInfo {
+    let path = music.0.join(file);
+    fs::write(&path, b"audio").unwrap();
+    SongInfo {
+        url: reqwest::Url::from_file_path(&path).unwrap().to_string(),
+        ..song()
+    }
+}
+
+#[tokio::test]
+async fn sidecar_lrc_next_to_audio() {
+    let music = TempDir::new();
+    let song = playing(&music, "07 晴天 #1.flac");
+    fs::write(music.0.join("07 晴天 #1.lrc"), format!("\u{feff}{LYRIC}")).unwrap();
+    fs::write(music.0.join("07 晴天 #1.txt"), "纯文本").unwrap();
+    let (client, _dir) = new_client(&sources(&[("local", local(&[], &[]))]));
+
+    let content = client.get_lyrics(&song).await.unwrap();
+
+    assert_eq!(content.lyric, LYRIC);
+}
+
+#[tokio::test]
+async fn sidecar_txt_when_no_lrc() {
+    let music = TempDir::new();
+    let song = playing(&music, "qingtian.mp3");
+    fs::write(music.0.join("qingtian.txt"), "故事的小黄花\n").unwrap();
+    let (client, _dir) = new_client(&sources(&[("local", local(&[], &[]))]));
+
+    let content = client.get_lyrics(&song).await.unwrap();
+
+    assert_eq!(content.lyric, "故事的小黄花\n");
+}
+
+#[tokio::test]
+async fn lyric_folder_matches_patterns_in_order() {
+    let lyrics = TempDir::new();
+    fs::write(lyrics.0.join("晴天.lrc"), "[00:01.00]title only\n").unwrap();
+    fs::write(lyrics.0.join("周杰伦 - 晴天.LRC"), LYRIC).unwrap();
+    fs::write(lyrics.0.join("周杰伦 - 七里香.lrc"), "[00:01.00]other\n").unwrap();
+    let (client, _dir) = new_client(&sources(&[("local", local(&[&lyrics], &[]))]));
+
+    let list = client.get_search(&song()).await.unwrap();
+    assert_eq!(list.len(), 2);
+    assert!(list[0].params[0].1.ends_with("周杰伦 - 晴天.LRC"));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+    assert_eq!(content.lyric, LYRIC);
+}
+
+#[tokio::test]
+async fn custom_patterns() {
+    let lyrics = TempDir::new();
+    fs::write(lyrics.0.join("叶惠美_晴天.lrc"), LYRIC).unwrap();
+    let (client, _dir) = new_client(&sources(&[(
+        "local",
+        local(&[&lyrics], &["{album}_{title}"]),
+    )]));
+
+    assert_eq!(client.get_lyrics(&song()).await.unwrap().lyric, LYRIC);
+}
+
+#[tokio::test]
+async fn local_takes_precedence_and_is_not_cached() {
+    let lyrics = TempDir::new();
+    fs::write(lyrics.0.join("晴天.lrc"), LYRIC).unwrap();
+    let server = MockServer::start().await;
+    server
+        .route("/api/search/get/", [Reply::fixture("netease/search.json")])
+        .route("/api/song/lyric/v1", [Reply::fixture("netease/lyric.json")]);
+    let (client, _dir) = new_client(&sources(&[
+        ("netease", server.settings()),
+        ("local", local(&[&lyrics], &[])),
+    ]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(content.lyric, LYRIC);
+    assert!(server.requests().is_empty());
+    assert!(client.cache.get(&song()).await.is_none());
+
+    // 删除本地歌词后使用网络来源
+    fs::remove_file(lyrics.0.join("晴天.lrc")).unwrap();
+    let content = client.get_lyrics(&song()).await.unwrap();
+    assert!(content.lyric.contains("<00:01.00>故事"));
+}
+
+#[tokio::test]
+async fn manual_local_download_is_not_cached() {
+    let lyrics = TempDir::new();
+    fs::write(lyrics.0.join("晴天.lrc"), LYRIC).unwrap();
+    let (client, _dir) = new_client(&sources(&[("local", local(&[&lyrics], &[]))]));
+
+    let list = client.get_search(&song()).await.unwrap();
+    client.download(&song(), &list[0]).await.unwrap();
+
+    assert!(client.cache.entry(&song()).await.is_none());
+}
+
+#[tokio::test]
+async fn non_file_urls_are_ignored() {
+    let song = SongInfo {
+        url: "https://example.com/stream.mp3".to_string(),
+        ..song()
+    };
+    let (client, _dir) = new_client(&sources(&[("local", local(&[], &[]))]));
+
+    assert!(client.get_lyrics(&song).await.is_err());
+}
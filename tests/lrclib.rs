@@ -0,0 +1,95 @@
+mod common;
+
+use common::{MockServer, Reply, new_client, sources};
+use lyrics_next::song::SongInfo;
+
+const GET: &str = "/api/get";
+const SEARCH: &str = "/api/search";
+
+fn song() -> SongInfo {
+    SongInfo {
+        title: "I Want to Live".to_string(),
+        artist: "Borislav Slavov".to_string(),
+        album: "Baldur's Gate 3 (Original Game Soundtrack)".to_string(),
+        duration: 233.4,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn exact_match_prefers_synced_lyrics() {
+    let server = MockServer::start().await;
+    server.route(GET, [Reply::fixture("lrclib/get.json")]);
+    let (client, _dir) = new_client(&sources(&[("lrclib", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    let request = &server.requests()[0];
+    assert!(request.starts_with(GET));
+    assert!(request.contains("track_name=I+Want+to+Live"), "{request}");
+    assert!(request.contains("duration=233"), "{request}");
+    assert!(content.lyric.starts_with("[00:17.12] I feel your breath"));
+    assert_eq!(server.hits(SEARCH), 0);
+}
+
+#[tokio::test]
+async fn falls_back_to_search() {
+    let server = MockServer::start().await;
+    server
+        .route(
+            GET,
+            [Reply::Body(
+                404,
+                common::fixture("lrclib/get_not_found.json"),
+            )],
+        )
+        .route(SEARCH, [Reply::fixture("lrclib/search.json")])
+        .route(
+            "/api/get/3396227",
+            [Reply::fixture("lrclib/get_plain.json")],
+        );
+    let (client, _dir) = new_client(&sources(&[("lrclib", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(server.hits("/api/get/3396227"), 1);
+    assert!(content.lyric.starts_with("I feel your breath"));
+}
+
+#[tokio::test]
+async fn search_skips_instrumentals() {
+    let server = MockServer::start().await;
+    server.route(SEARCH, [Reply::fixture("lrclib/search.json")]);
+    let (client, _dir) = new_client(&sources(&[("lrclib", server.settings())]));
+
+    let list = client.get_search(&song()).await.unwrap();
+
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[1].source, "LRCLIB");
+    assert_eq!(list[1].duration, Some(233.0));
+    assert!(
+        list[1]
+            .params
+            .contains(&("id".to_string(), "3396227".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn incomplete_song_info_skips_exact_match() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("lrclib/search.json")])
+        .route(
+            "/api/get/3396227",
+            [Reply::fixture("lrclib/get_plain.json")],
+        );
+    let (client, _dir) = new_client(&sources(&[("lrclib", server.settings())]));
+    let song = SongInfo {
+        album: String::new(),
+        duration: 0.0,
+        ..song()
+    };
+
+    assert!(client.get_lyrics(&song).await.is_ok());
+    assert_eq!(server.hits(GET), 0);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{MockServer, Reply, new_client, song, sources};
+use lyrics_next::error::LyricsError;
+
+const SEARCH: &str = "/api/search/get/";
+const LYRIC: &str = "/api/song/lyric/v1";
+
+#[tokio::test]
+async fn search_maps_songs() {
+    let server = MockServer::start().await;
+    server.route(SEARCH, [Reply::fixture("netease/search.json")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let list = client.get_search(&song()).await.unwrap();
+
+    assert_eq!(list.len(), 3);
+    assert_eq!(list[1].source, "Netease");
+    assert_eq!(list[1].title, "晴天");
+    assert_eq!(list[1].artist, "周杰伦");
+    assert_eq!(list[1].album, "叶惠美");
+    assert_eq!(list[1].duration, Some(269.0));
+    assert!(
+        list[1]
+            .params
+            .contains(&("id".to_string(), "186016".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn fetch_picks_studio_version_and_converts_yrc() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("netease/search.json")])
+        .route(LYRIC, [Reply::fixture("netease/lyric.json")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    let download = server
+        .requests()
+        .into_iter()
+        .find(|r| r.starts_with(LYRIC))
+        .unwrap();
+    assert!(download.contains("id=186016"), "{download}");
+    assert!(
+        content
+            .lyric
+            .contains("[00:01.00]<00:01.00>故事<00:02.00>的")
+    );
+    assert!(!content.lyric.contains("作词"));
+    assert!(
+        content
+            .translation
+            .unwrap()
+            .contains("The little yellow flower")
+    );
+    assert_eq!(content.romanization, None);
+    assert!(client.cache.get(&song()).await.is_some());
+}
+
+#[tokio::test]
+async fn download_falls_back_to_lrc() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("netease/search.json")])
+        .route(LYRIC, [Reply::fixture("netease/lyric_lrc_only.json")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert!(content.lyric.starts_with("[00:01.00]故事的小黄花"));
+    assert_eq!(content.translation, None);
+}
+
+#[tokio::test]
+async fn empty_search_is_not_found() {
+    let server = MockServer::start().await;
+    server.route(SEARCH, [Reply::fixture("netease/search_empty.json")]);
+    let (client, _dir) = new_client(&sources(&[("netease", server.settings())]));
+
+    let err = client.get_search(&song()).await.unwrap_err();
+
+    assert!(matches!(err, LyricsError::NoLyricsFound), "{err}");
+}
+
+#[tokio::test]
+async fn duration_tolerance_comes_from_config() {
+    let server = MockServer::start().await;
+    server.route(SEARCH, [Reply::fixture("netease/search.json")]);
+    let mut config = sources(&[("netease", server.settings())]);
+    // 录制结果比歌曲短 0.5 秒
+    config.duration_tolerance = 0.1;
+    let (client, _dir) = new_client(&config);
+
+    let err = client.get_lyrics(&song()).await.unwrap_err();
+
+    assert!(matches!(err, LyricsError::NoLyricsFound), "{err}");
+    assert_eq!(server.hits(LYRIC), 0);
+}
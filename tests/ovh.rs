@@ -0,0 +1,122 @@
+mod common;
+
+use common::{MockServer, Reply, new_client, sources};
+use lyrics_next::song::SongInfo;
+
+const SUGGEST: &str = "/suggest/coldplay%20yellow";
+const DIRECT: &str = "/v1/coldplay/yellow";
+const FOUND: &str = "/v1/Coldplay/Yellow";
+
+fn song() -> SongInfo {
+    SongInfo {
+        title: "yellow".to_string(),
+        artist: "coldplay".to_string(),
+        album: "Parachutes".to_string(),
+        duration: 266.8,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn direct_lookup_returns_plain_lyrics() {
+    let server = MockServer::start().await;
+    server.route(DIRECT, [Reply::fixture("ovh/lyrics.json")]);
+    let (client, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(
+        content.lyric,
+        "Look at the stars\nLook how they shine for you"
+    );
+    assert_eq!(server.hits(SUGGEST), 0);
+}
+
+#[tokio::test]
+async fn direct_lookup_scores_below_confirmed_match() {
+    let server = MockServer::start().await;
+    server
+        .route(
+            DIRECT,
+            [
+                Reply::fixture("ovh/lyrics.json"),
+                Reply::Body(404, common::fixture("ovh/not_found.json")),
+            ],
+        )
+        .route(SUGGEST, [Reply::fixture("ovh/suggest.json")])
+        .route(FOUND, [Reply::fixture("ovh/lyrics.json")]);
+
+    let (direct, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+    direct.get_lyrics(&song()).await.unwrap();
+    let (searched, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+    searched.get_lyrics(&song()).await.unwrap();
+    assert_eq!(server.hits(FOUND), 1);
+
+    // 直接获取的结果专辑与时长未知，低于搜索确认的结果
+    let score = |entry: Option<lyrics_next::cache::CacheEntry>| entry.unwrap().score.unwrap();
+    let direct = score(direct.cache.entry(&song()).await);
+    let searched = score(searched.cache.entry(&song()).await);
+    assert!(direct < searched, "{direct} {searched}");
+}
+
+#[tokio::test]
+async fn path_segments_are_encoded() {
+    let server = MockServer::start().await;
+    server.route(
+        "/v1/AC%2FDC/Highway%20to%20Hell%3F",
+        [Reply::fixture("ovh/lyrics.json")],
+    );
+    let (client, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+    let song = SongInfo {
+        title: "Highway to Hell?".to_string(),
+        artist: "AC/DC".to_string(),
+        ..Default::default()
+    };
+
+    assert!(
+        client.get_lyrics(&song).await.is_ok(),
+        "{:?}",
+        server.requests()
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_suggest() {
+    let server = MockServer::start().await;
+    server
+        .route(
+            DIRECT,
+            [Reply::Body(404, common::fixture("ovh/not_found.json"))],
+        )
+        .route(SUGGEST, [Reply::fixture("ovh/suggest.json")])
+        .route(FOUND, [Reply::fixture("ovh/lyrics.json")]);
+    let (client, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(server.hits(FOUND), 1);
+    assert!(content.lyric.starts_with("Look at the stars"));
+}
+
+#[tokio::test]
+async fn search_maps_suggestions() {
+    let server = MockServer::start().await;
+    server.route(SUGGEST, [Reply::fixture("ovh/suggest.json")]);
+    let (client, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+
+    let list = client.get_search(&song()).await.unwrap();
+
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[1].source, "LyricsOvh");
+    assert_eq!(list[1].album, "Parachutes");
+    assert_eq!(list[1].duration, Some(266.0));
+}
+
+#[tokio::test]
+async fn missing_lyrics_is_an_error() {
+    let server = MockServer::start().await;
+    server.route(DIRECT, [Reply::fixture("ovh/not_found.json")]);
+    let (client, _dir) = new_client(&sources(&[("ovh", server.settings())]));
+
+    assert!(client.get_lyrics(&song()).await.is_err());
+}
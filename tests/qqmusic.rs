@@ -0,0 +1,121 @@
+mod common;
+
+use common::{MockServer, Reply, fixture, new_client, song, sources};
+use lyrics_next::song::QrcParser;
+
+const SEARCH: &str = "/soso/fcgi-bin/client_search_cp";
+const QRC: &str = "/qqmusic/fcgi-bin/lyric_download.fcg";
+const LRC: &str = "/lyric/fcgi-bin/fcg_query_lyric_new.fcg";
+
+#[tokio::test]
+async fn search_maps_songs() {
+    let server = MockServer::start().await;
+    server.route(SEARCH, [Reply::fixture("qqmusic/search.json")]);
+    let (client, _dir) = new_client(&sources(&[("qq", server.settings())]));
+
+    let list = client.get_search(&song()).await.unwrap();
+
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].source, "QQMusic");
+    assert_eq!(list[0].duration, Some(269.0));
+    assert!(
+        list[0]
+            .params
+            .contains(&("songmid".to_string(), "0039MnYb0qxYhV".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn qrc_is_decrypted_with_translation() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("qqmusic/search.json")])
+        .route(QRC, [Reply::fixture("qqmusic/qrc.xml")]);
+    let (client, _dir) = new_client(&sources(&[("qq", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(server.hits(LRC), 0);
+    let expected = QrcParser::parse(&fixture("qqmusic/qrc_plain.xml"))
+        .await
+        .unwrap();
+    assert_eq!(content.lyric, expected.to_lrc());
+    assert!(
+        content
+            .lyric
+            .contains("[00:01.00]<00:01.00>故事<00:02.00>的<00:02.50>小黄花")
+    );
+    assert!(
+        content
+            .translation
+            .unwrap()
+            .contains("[00:04.00]Drifting since the year I was born")
+    );
+}
+
+#[tokio::test]
+async fn headers_follow_base_url() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("qqmusic/search.json")])
+        .route(QRC, [Reply::fixture("qqmusic/qrc.xml")]);
+    let (client, _dir) = new_client(&sources(&[("qq", server.settings())]));
+
+    client.get_lyrics(&song()).await.unwrap();
+
+    let host = server.url().trim_start_matches("http://").to_string();
+    let referer = format!("{}/n/ryqq/player", server.url());
+    for path in [SEARCH, QRC] {
+        assert_eq!(server.header(path, "host"), Some(host.clone()));
+        assert_eq!(server.header(path, "referer"), Some(referer.clone()));
+    }
+    assert_eq!(server.header(SEARCH, "origin"), Some(server.url()));
+}
+
+#[tokio::test]
+async fn unavailable_qrc_falls_back_to_lrc() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("qqmusic/search.json")])
+        .route(QRC, [Reply::fixture("qqmusic/qrc_unavailable.xml")])
+        .route(LRC, [Reply::fixture("qqmusic/lyric.json")]);
+    let (client, _dir) = new_client(&sources(&[("qq", server.settings())]));
+
+    let content = client.get_lyrics(&song()).await.unwrap();
+
+    assert_eq!(server.hits(QRC), 1);
+    assert!(
+        server
+            .requests()
+            .iter()
+            .any(|r| r.starts_with(QRC) && r.contains("musicid=97773"))
+    );
+    assert!(
+        server
+            .requests()
+            .iter()
+            .any(|r| r.starts_with(LRC) && r.contains("songmid=0039MnYb0qxYhV"))
+    );
+    assert!(content.lyric.contains("[00:01.00]故事的小黄花"));
+    assert!(
+        content
+            .translation
+            .unwrap()
+            .contains("The little yellow flower")
+    );
+}
+
+#[tokio::test]
+async fn invalid_base64_lyric_fails() {
+    let server = MockServer::start().await;
+    server
+        .route(SEARCH, [Reply::fixture("qqmusic/search.json")])
+        .route(
+            LRC,
+            [Reply::ok(r#"{"retcode":0,"lyric":"%%%not base64%%%"}"#)],
+        );
+    let (client, _dir) = new_client(&sources(&[("qq", server.settings())]));
+
+    assert!(client.get_lyrics(&song()).await.is_err());
+    assert!(client.cache.get(&song()).await.is_none());
+}